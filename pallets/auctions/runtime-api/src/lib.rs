@@ -0,0 +1,15 @@
+#![cfg_attr(not(feature = "std"), no_std)]
+// The `unnecessary_mut_passed` warning originates from `decl_runtime_apis` macro.
+#![allow(clippy::unnecessary_mut_passed)]
+
+use codec::Codec;
+
+sp_api::decl_runtime_apis! {
+    pub trait AuctionApi<AuctionId, Balance> where
+        AuctionId: Codec,
+        Balance: Codec,
+    {
+        /// Price a dutch auction can be bought at right now, `None` for other auctions.
+        fn current_price(id: AuctionId) -> Option<Balance>;
+    }
+}
@@ -1,19 +1,94 @@
 #![cfg_attr(not(feature = "std"), no_std)]
 
+use codec::{Decode, Encode};
 use frame_support::{
-    pallet_prelude::*,
-    storage::types::ValueQuery,
-    traits::{Currency, ReservableCurrency},
-    transactional, PalletId,
+    pallet_prelude::*, traits::BalanceStatus, transactional, BoundedVec, PalletId,
 };
 use frame_system::pallet_prelude::*;
-use orml_traits::{Auction, AuctionHandler, Change, OnNewBidResult};
-use sp_runtime::{traits::AccountIdConversion, DispatchResult};
+use orml_traits::{
+    Auction, AuctionHandler, Change, MultiCurrency, MultiReservableCurrency, OnNewBidResult, NFT,
+};
+use sp_runtime::{
+    helpers_128bit::multiply_by_rational,
+    traits::{AccountIdConversion, Hash as HashT, SaturatedConversion, Saturating, Zero},
+    DispatchResult, Permill,
+};
+use sp_std::vec::Vec;
 
 pub use pallet::*;
 
-type BalanceOf<T> =
-    <<T as Config>::Currency as Currency<<T as frame_system::Config>::AccountId>>::Balance;
+#[cfg(test)]
+mod mock;
+#[cfg(test)]
+mod tests;
+
+type BalanceOf<T> = <<T as Config>::MultiCurrency as MultiCurrency<
+    <T as frame_system::Config>::AccountId,
+>>::Balance;
+type CurrencyIdOf<T> = <<T as Config>::MultiCurrency as MultiCurrency<
+    <T as frame_system::Config>::AccountId,
+>>::CurrencyId;
+
+/// How the winner of an auction is decided.
+#[derive(Encode, Decode, Clone, Copy, RuntimeDebug, PartialEq, Eq)]
+pub enum AuctionKind<Balance, BlockNumber> {
+    /// Ascending bids, the highest bid at the end wins.
+    English,
+    /// The price falls from the start price to `floor_price` between start and end,
+    /// dropping every `step` blocks. The first buyer accepting the current price wins.
+    Dutch {
+        floor_price: Balance,
+        step: BlockNumber,
+    },
+    /// Bidders commit to a hidden bid until `reveal_start` and reveal it before the end.
+    /// The highest revealed bid wins and pays the second highest one.
+    Sealed { reveal_start: BlockNumber },
+}
+
+/// A sealed bid, committed as the hash of `(auction id, bidder, amount, salt)`.
+#[derive(Encode, Decode, Clone, RuntimeDebug, PartialEq, Eq)]
+pub struct SealedBid<Hash, Balance> {
+    pub commitment: Hash,
+    /// Reserved from the bidder, needs to cover the revealed amount
+    pub deposit: Balance,
+    pub revealed: Option<Balance>,
+}
+
+/// One or several NFTs listed for auction as a single lot.
+#[derive(Encode, Decode, Clone, RuntimeDebug, PartialEq, Eq)]
+pub struct AuctionListing<
+    AccountId,
+    Balance,
+    ClassId,
+    TokenId,
+    BlockNumber,
+    CurrencyId,
+    MaxBundleSize,
+> {
+    pub kind: AuctionKind<Balance, BlockNumber>,
+    /// Account which listed the nfts and gets paid once they are sold
+    pub seller: AccountId,
+    /// Currency the bids and every payment of the auction are made in
+    pub currency_id: CurrencyId,
+    /// Minimum price for the first bid
+    pub start_price: Balance,
+    /// A bid at or above this price ends the auction right away
+    pub buy_now_price: Option<Balance>,
+    /// Class and token ids of the nfts in the lot
+    pub nfts: BoundedVec<(ClassId, TokenId), MaxBundleSize>,
+    /// Number of blocks the end was pushed out by late bids
+    pub extended: BlockNumber,
+}
+
+pub type AuctionListingOf<T> = AuctionListing<
+    <T as frame_system::Config>::AccountId,
+    BalanceOf<T>,
+    <T as orml_nft::Config>::ClassId,
+    <T as orml_nft::Config>::TokenId,
+    <T as frame_system::Config>::BlockNumber,
+    CurrencyIdOf<T>,
+    <T as Config>::MaxBundleSize,
+>;
 
 #[frame_support::pallet]
 pub mod pallet {
@@ -21,16 +96,61 @@ pub mod pallet {
 
     #[pallet::config]
     pub trait Config:
-        frame_system::Config + orml_auction::Config<Balance = BalanceOf<Self>> + orml_nft::Config
+        frame_system::Config + orml_auction::Config<Balance = BalanceOf<Self>> + kodadot_nft::Config
     {
         type Event: From<Event<Self>> + IsType<<Self as frame_system::Config>::Event>;
 
-        /// The pallet in charge of currency transfers
-        type Currency: ReservableCurrency<Self::AccountId>;
+        /// The pallet in charge of currency transfers, auctions can be priced in any of its
+        /// currencies
+        type MultiCurrency: MultiReservableCurrency<Self::AccountId>;
+
+        /// Account receiving the marketplace fees and the deposits of sealed bids which were
+        /// never revealed
+        type TreasuryAccount: Get<Self::AccountId>;
 
         /// The pallet's module id - used to keep NFTs which are on auction
         #[pallet::constant]
         type PalletId: Get<PalletId>;
+
+        /// Minimum increment, relative to the last bid, that a new bid must add
+        #[pallet::constant]
+        type MinimumBidIncrement: Get<Permill>;
+
+        /// Bids placed within this many blocks of the end push the end out by as many blocks
+        #[pallet::constant]
+        type ExtensionWindow: Get<Self::BlockNumber>;
+
+        /// Maximum number of blocks an auction can be extended by in total
+        #[pallet::constant]
+        type MaxExtension: Get<Self::BlockNumber>;
+
+        /// Maximum number of sealed bids a single auction accepts
+        #[pallet::constant]
+        type MaxSealedBids: Get<u32>;
+
+        /// Cut of every settlement taken by the marketplace until governance sets another one
+        #[pallet::constant]
+        type MarketplaceFee: Get<Permill>;
+
+        /// Maximum cut of every settlement governance can set as marketplace fee
+        #[pallet::constant]
+        type MaxMarketplaceFee: Get<Permill>;
+
+        /// Maximum number of nfts listed together as a single lot
+        #[pallet::constant]
+        type MaxBundleSize: Get<u32>;
+
+        /// Minimum number of blocks between the start and the end of an auction
+        #[pallet::constant]
+        type MinAuctionDuration: Get<Self::BlockNumber>;
+
+        /// Maximum number of blocks between the start and the end of an auction
+        #[pallet::constant]
+        type MaxAuctionDuration: Get<Self::BlockNumber>;
+
+        /// Number of blocks after a failed settlement before its seller can abandon it
+        #[pallet::constant]
+        type SettlementGracePeriod: Get<Self::BlockNumber>;
     }
 
     #[pallet::error]
@@ -41,26 +161,164 @@ pub mod pallet {
         FailedNftTransfer,
         /// Failed to register auction
         AuctionNotRegistered,
+        /// No auction is registered under this id
+        AuctionNotFound,
+        /// This can only be done by the seller of the auction
+        NotAuctionOwner,
+        /// The auction already received a bid and can't be cancelled anymore
+        AuctionHasBids,
+        /// The buy now price can't be lower than the start price
+        InvalidBuyNowPrice,
+        /// Dutch auctions need a floor price at most the start price, a non zero step and
+        /// no buy now price
+        InvalidDutchAuction,
+        /// This can only be done on a dutch auction
+        NotDutchAuction,
+        /// The auction hasn't started yet
+        AuctionNotStarted,
+        /// Sellers can't buy their own nft
+        CannotBuyOwnAuction,
+        /// The current price is above what the buyer is willing to pay
+        PriceAboveLimit,
+        /// Sealed auctions need a reveal phase starting strictly between start and end and
+        /// no buy now price
+        InvalidSealedAuction,
+        /// This can only be done on a sealed auction
+        NotSealedAuction,
+        /// Sealed bids can only be committed before the reveal phase
+        NotCommitPhase,
+        /// Sealed bids can only be revealed during the reveal phase
+        NotRevealPhase,
+        /// The bidder already committed a bid to this auction
+        AlreadyCommitted,
+        /// The auction doesn't accept more sealed bids
+        TooManySealedBids,
+        /// No sealed bid from this bidder on this auction
+        SealedBidNotFound,
+        /// The sealed bid was already revealed
+        AlreadyRevealed,
+        /// The revealed bid and salt don't match the commitment
+        InvalidReveal,
+        /// The revealed bid is higher than its deposit
+        BidExceedsDeposit,
+        /// Sealed bid deposits need to be non zero and cover at least the start price
+        DepositTooLow,
+        /// No failed settlement is recorded for this auction
+        SettlementNotFound,
+        /// The winning bid isn't fully reserved anymore
+        WinningBidNotReserved,
+        /// The auction would start before the current block
+        StartInPast,
+        /// The auction would end before it starts
+        EndBeforeStart,
+        /// The auction would be shorter than the minimum duration
+        AuctionTooShort,
+        /// The auction would be longer than the maximum duration
+        AuctionTooLong,
+        /// A lot needs at least one nft
+        InvalidBundleSize,
+        /// The marketplace fee can't be higher than the maximum marketplace fee
+        FeeTooHigh,
+        /// This can only be done by the winner or the seller of the auction
+        NotSettlementParty,
+        /// The seller can only abandon a settlement once the grace period is over
+        GracePeriodNotOver,
     }
 
-    /// Store auction info (owner, start price, class id, token id).
-    ///
-    /// Returns the default value if auction info not set or removed.
+    /// Store auction listings.
     #[pallet::storage]
     #[pallet::getter(fn auctions)]
-    pub type Auctions<T: Config> = StorageMap<
+    pub type Auctions<T: Config> =
+        StorageMap<_, Twox64Concat, T::AuctionId, AuctionListingOf<T>, OptionQuery>;
+
+    /// Store sealed bids by auction id and bidder.
+    #[pallet::storage]
+    #[pallet::getter(fn sealed_bids)]
+    pub type SealedBids<T: Config> = StorageDoubleMap<
         _,
         Twox64Concat,
         T::AuctionId,
-        (T::AccountId, BalanceOf<T>, T::ClassId, T::TokenId),
-        ValueQuery,
+        Twox64Concat,
+        T::AccountId,
+        SealedBid<T::Hash, BalanceOf<T>>,
+        OptionQuery,
     >;
 
+    /// Store auctions whose settlement failed (listing, winner, block of the failure), their
+    /// nfts and winning bid stay in escrow until the settlement is retried or abandoned.
+    #[pallet::storage]
+    #[pallet::getter(fn failed_settlements)]
+    pub type FailedSettlements<T: Config> = StorageMap<
+        _,
+        Twox64Concat,
+        T::AuctionId,
+        (
+            AuctionListingOf<T>,
+            Option<(T::AccountId, BalanceOf<T>)>,
+            T::BlockNumber,
+        ),
+        OptionQuery,
+    >;
+
+    #[pallet::type_value]
+    pub fn DefaultFee<T: Config>() -> Permill {
+        T::MarketplaceFee::get()
+    }
+
+    /// Store the cut of every settlement taken by the marketplace.
+    #[pallet::storage]
+    #[pallet::getter(fn marketplace_fee)]
+    pub type Fee<T: Config> = StorageValue<_, Permill, ValueQuery, DefaultFee<T>>;
+
+    /// Store the number of sealed bids committed to an auction.
+    #[pallet::storage]
+    #[pallet::getter(fn sealed_bid_count)]
+    pub type SealedBidCount<T: Config> = StorageMap<_, Twox64Concat, T::AuctionId, u32, ValueQuery>;
+
     #[pallet::event]
     #[pallet::generate_deposit(pub(crate) fn deposit_event)]
     pub enum Event<T: Config> {
-        /// Listed a NFT on sale. \[auction id, class_id, token id, start price\]
-        ListedNFT(T::AuctionId, T::ClassId, T::TokenId, BalanceOf<T>),
+        /// Listed a lot of NFTs on sale. \[auction id, nfts, start price\]
+        ListedNFT(T::AuctionId, Vec<(T::ClassId, T::TokenId)>, BalanceOf<T>),
+        /// A bid was accepted. \[auction id, bidder, amount\]
+        BidPlaced(T::AuctionId, T::AccountId, BalanceOf<T>),
+        /// A revealed sealed bid was below the start price and got refunded when the auction
+        /// closed. Open bids which are turned down aren't reported by an event, they make the
+        /// `bid` extrinsic fail with `BidNotAccepted`. \[auction id, bidder, amount\]
+        BidRejected(T::AuctionId, T::AccountId, BalanceOf<T>),
+        /// A bid reached the buy now price, the auction ends this block. \[auction id, buyer, amount\]
+        BoughtNow(T::AuctionId, T::AccountId, BalanceOf<T>),
+        /// A late bid extended the auction. \[auction id, new end, total extension\]
+        AuctionExtended(T::AuctionId, T::BlockNumber, T::BlockNumber),
+        /// The seller cancelled a listing. \[auction id\]
+        ListingCancelled(T::AuctionId),
+        /// A sealed bid was committed. \[auction id, bidder, deposit\]
+        BidCommitted(T::AuctionId, T::AccountId, BalanceOf<T>),
+        /// A sealed bid was revealed. \[auction id, bidder, amount\]
+        BidRevealed(T::AuctionId, T::AccountId, BalanceOf<T>),
+        /// The deposit of a sealed bid which wasn't revealed was slashed. \[auction id, bidder, deposit\]
+        SealedBidSlashed(T::AuctionId, T::AccountId, BalanceOf<T>),
+        /// The winning bid was paid out. \[auction id, seller, seller amount, royalties amount\]
+        Settled(T::AuctionId, T::AccountId, BalanceOf<T>, BalanceOf<T>),
+        /// Paid the royalty of a nft class from a settlement. \[auction id, recipient, amount\]
+        RoyaltyPaid(T::AuctionId, T::AccountId, BalanceOf<T>),
+        /// The marketplace fee was taken from a settlement. \[auction id, amount\]
+        FeeCharged(T::AuctionId, BalanceOf<T>),
+        /// Governance changed the marketplace fee. \[fee\]
+        FeeSet(Permill),
+        /// Settling an ended auction failed, it can be retried. \[auction id, error\]
+        SettlementFailed(T::AuctionId, DispatchError),
+        /// A failed settlement was retried successfully. \[auction id\]
+        SettlementRetried(T::AuctionId),
+        /// A failed settlement was given up, the winning bid went back to the winner.
+        /// \[auction id\]
+        SettlementAbandoned(T::AuctionId),
+        /// An auction was won. \[auction id, winner, price\]
+        AuctionWon(T::AuctionId, T::AccountId, BalanceOf<T>),
+        /// An auction ended without a winner. \[auction id\]
+        AuctionExpiredUnsold(T::AuctionId),
+        /// The nfts of an auction went back to their seller. \[auction id, seller\]
+        NftReturned(T::AuctionId, T::AccountId),
     }
 
     #[pallet::pallet]
@@ -71,48 +329,553 @@ pub mod pallet {
 
     #[pallet::call]
     impl<T: Config> Pallet<T> {
-        /// List a NFT for auction.
-        /// - `nft` should be the class and token ids of the nft
+        /// List one or several NFTs for auction as a single lot.
+        /// - `nfts` should be the class and token ids of the nfts, at most `MaxBundleSize` of them
         /// - `start_price` is the minimum price for any bid
         /// - `start` is when the auction starts
         /// - `end` is when the auction ends
+        /// - `buy_now_price` optionally lets a bid at or above it end the auction right away
+        /// - `kind` is either an english, a dutch or a sealed bid auction
+        /// - `currency_id` is the currency bids are made in
         #[pallet::weight(1_000)]
         #[transactional]
         fn list(
             origin: OriginFor<T>,
-            nft: (T::ClassId, T::TokenId),
+            nfts: BoundedVec<(T::ClassId, T::TokenId), T::MaxBundleSize>,
             start_price: BalanceOf<T>,
             start: T::BlockNumber,
             end: T::BlockNumber,
+            buy_now_price: Option<BalanceOf<T>>,
+            kind: AuctionKind<BalanceOf<T>, T::BlockNumber>,
+            currency_id: CurrencyIdOf<T>,
         ) -> DispatchResult {
             let who = ensure_signed(origin)?;
 
             ensure!(
-                orml_nft::Pallet::<T>::is_owner(&who, nft),
-                Error::<T>::NotOwnerOfNft
+                start >= frame_system::Pallet::<T>::block_number(),
+                Error::<T>::StartInPast
             );
+            ensure!(end > start, Error::<T>::EndBeforeStart);
+            let duration = end - start;
             ensure!(
-                orml_nft::Pallet::<T>::transfer(&who, &T::PalletId::get().into_account(), nft)
-                    .is_ok(),
-                Error::<T>::FailedNftTransfer
+                duration >= T::MinAuctionDuration::get(),
+                Error::<T>::AuctionTooShort
             );
+            ensure!(
+                duration <= T::MaxAuctionDuration::get(),
+                Error::<T>::AuctionTooLong
+            );
+
+            if let Some(price) = buy_now_price {
+                ensure!(price >= start_price, Error::<T>::InvalidBuyNowPrice);
+            }
+            if let AuctionKind::Dutch { floor_price, step } = kind {
+                ensure!(
+                    floor_price <= start_price && !step.is_zero() && buy_now_price.is_none(),
+                    Error::<T>::InvalidDutchAuction
+                );
+            }
+            if let AuctionKind::Sealed { reveal_start } = kind {
+                ensure!(
+                    start < reveal_start && reveal_start < end && buy_now_price.is_none(),
+                    Error::<T>::InvalidSealedAuction
+                );
+            }
+
+            ensure!(!nfts.is_empty(), Error::<T>::InvalidBundleSize);
+            for nft in nfts.iter() {
+                ensure!(
+                    orml_nft::Pallet::<T>::is_owner(&who, *nft),
+                    Error::<T>::NotOwnerOfNft
+                );
+            }
+            Self::transfer_nfts(&who, &T::PalletId::get().into_account(), &nfts)?;
+            let listed = nfts.to_vec();
 
             let auction_id = orml_auction::Pallet::<T>::new_auction(start, Some(end));
             ensure!(auction_id.is_ok(), Error::<T>::AuctionNotRegistered);
 
             Auctions::<T>::insert(
                 auction_id.expect("we did a is_ok check; qed"),
-                (who, start_price, nft.0, nft.1),
+                AuctionListing {
+                    kind,
+                    seller: who,
+                    currency_id,
+                    start_price,
+                    buy_now_price,
+                    nfts,
+                    extended: Zero::zero(),
+                },
             );
 
             Self::deposit_event(Event::ListedNFT(
                 auction_id.expect("we did a is_ok check; qed"),
-                nft.0,
-                nft.1,
+                listed,
                 start_price,
             ));
             Ok(())
         }
+
+        /// Cancel a listing and get the NFTs back. Only possible before the auction
+        /// received its first bid, which can't happen before `start`.
+        /// - `id` is the auction to cancel
+        #[pallet::weight(1_000)]
+        #[transactional]
+        fn cancel(origin: OriginFor<T>, id: T::AuctionId) -> DispatchResult {
+            let who = ensure_signed(origin)?;
+
+            let listing = Auctions::<T>::get(id).ok_or(Error::<T>::AuctionNotFound)?;
+            ensure!(listing.seller == who, Error::<T>::NotAuctionOwner);
+
+            let auction =
+                orml_auction::Pallet::<T>::auction_info(id).ok_or(Error::<T>::AuctionNotFound)?;
+            ensure!(
+                auction.bid.is_none() && SealedBidCount::<T>::get(id).is_zero(),
+                Error::<T>::AuctionHasBids
+            );
+
+            orml_auction::Pallet::<T>::remove_auction(id);
+            Auctions::<T>::remove(id);
+
+            Self::transfer_nfts(&T::PalletId::get().into_account(), &who, &listing.nfts)?;
+
+            Self::deposit_event(Event::ListingCancelled(id));
+            Ok(())
+        }
+
+        /// Buy the NFTs of a dutch auction at its current price.
+        /// - `id` is the dutch auction to buy from
+        /// - `max_price` protects the buyer against paying more than expected
+        #[pallet::weight(1_000)]
+        #[transactional]
+        fn buy(origin: OriginFor<T>, id: T::AuctionId, max_price: BalanceOf<T>) -> DispatchResult {
+            let who = ensure_signed(origin)?;
+
+            let listing = Auctions::<T>::get(id).ok_or(Error::<T>::AuctionNotFound)?;
+            ensure!(
+                matches!(listing.kind, AuctionKind::Dutch { .. }),
+                Error::<T>::NotDutchAuction
+            );
+            ensure!(listing.seller != who, Error::<T>::CannotBuyOwnAuction);
+
+            let auction =
+                orml_auction::Pallet::<T>::auction_info(id).ok_or(Error::<T>::AuctionNotFound)?;
+            let now = frame_system::Pallet::<T>::block_number();
+            ensure!(now >= auction.start, Error::<T>::AuctionNotStarted);
+
+            let price = Self::current_price(id).ok_or(Error::<T>::AuctionNotFound)?;
+            ensure!(price <= max_price, Error::<T>::PriceAboveLimit);
+
+            T::MultiCurrency::reserve(listing.currency_id, &who, price)?;
+            orml_auction::Pallet::<T>::remove_auction(id);
+            Auctions::<T>::remove(id);
+
+            Self::deposit_event(Event::BoughtNow(id, who.clone(), price));
+            Self::settle(id, &listing, &Some((who, price)))
+        }
+
+        /// Commit a sealed bid to an auction.
+        /// - `id` is the sealed auction to bid on
+        /// - `commitment` is the hash of the SCALE encoded `(id, bidder, amount, salt)`, tying
+        /// it to the auction and the bidder so it can't be copied by another bidder
+        /// - `deposit` is reserved until the end and needs to cover the amount, it can be
+        /// higher to hide the amount but not lower than the start price
+        #[pallet::weight(1_000)]
+        #[transactional]
+        fn commit_bid(
+            origin: OriginFor<T>,
+            id: T::AuctionId,
+            commitment: T::Hash,
+            deposit: BalanceOf<T>,
+        ) -> DispatchResult {
+            let who = ensure_signed(origin)?;
+
+            let (reveal_start, currency_id) = Self::sealed_phases(id)?;
+            let listing = Auctions::<T>::get(id).ok_or(Error::<T>::AuctionNotFound)?;
+            // sellers could otherwise raise the second price paid by the winner
+            ensure!(listing.seller != who, Error::<T>::CannotBuyOwnAuction);
+            // bids below the start price are refunded anyway, cheaper deposits would only
+            // let anyone fill up the sealed bids for free
+            ensure!(
+                !deposit.is_zero() && deposit >= listing.start_price,
+                Error::<T>::DepositTooLow
+            );
+            let auction =
+                orml_auction::Pallet::<T>::auction_info(id).ok_or(Error::<T>::AuctionNotFound)?;
+            let now = frame_system::Pallet::<T>::block_number();
+            ensure!(
+                now >= auction.start && now < reveal_start,
+                Error::<T>::NotCommitPhase
+            );
+            ensure!(
+                !SealedBids::<T>::contains_key(id, &who),
+                Error::<T>::AlreadyCommitted
+            );
+
+            SealedBidCount::<T>::try_mutate(id, |count| -> DispatchResult {
+                ensure!(
+                    *count < T::MaxSealedBids::get(),
+                    Error::<T>::TooManySealedBids
+                );
+                *count += 1;
+                Ok(())
+            })?;
+            T::MultiCurrency::reserve(currency_id, &who, deposit)?;
+            SealedBids::<T>::insert(
+                id,
+                &who,
+                SealedBid {
+                    commitment,
+                    deposit,
+                    revealed: None,
+                },
+            );
+
+            Self::deposit_event(Event::BidCommitted(id, who, deposit));
+            Ok(())
+        }
+
+        /// Reveal a sealed bid.
+        /// - `id` is the sealed auction the bid was committed to
+        /// - `amount` and `salt` are the values the commitment was made from
+        #[pallet::weight(1_000)]
+        #[transactional]
+        fn reveal_bid(
+            origin: OriginFor<T>,
+            id: T::AuctionId,
+            amount: BalanceOf<T>,
+            salt: [u8; 32],
+        ) -> DispatchResult {
+            let who = ensure_signed(origin)?;
+
+            let (reveal_start, _) = Self::sealed_phases(id)?;
+            let now = frame_system::Pallet::<T>::block_number();
+            ensure!(now >= reveal_start, Error::<T>::NotRevealPhase);
+
+            SealedBids::<T>::try_mutate(id, &who, |bid| -> DispatchResult {
+                let bid = bid.as_mut().ok_or(Error::<T>::SealedBidNotFound)?;
+                ensure!(bid.revealed.is_none(), Error::<T>::AlreadyRevealed);
+                ensure!(
+                    T::Hashing::hash_of(&(id, &who, amount, salt)) == bid.commitment,
+                    Error::<T>::InvalidReveal
+                );
+                ensure!(amount <= bid.deposit, Error::<T>::BidExceedsDeposit);
+                bid.revealed = Some(amount);
+                Ok(())
+            })?;
+
+            Self::deposit_event(Event::BidRevealed(id, who, amount));
+            Ok(())
+        }
+
+        /// Retry the settlement of an ended auction which failed, anyone can call this.
+        /// - `id` is the auction to settle
+        #[pallet::weight(1_000)]
+        #[transactional]
+        fn retry_settlement(origin: OriginFor<T>, id: T::AuctionId) -> DispatchResult {
+            ensure_signed(origin)?;
+
+            let (listing, winner, _) =
+                FailedSettlements::<T>::take(id).ok_or(Error::<T>::SettlementNotFound)?;
+            Self::settle(id, &listing, &winner)?;
+
+            Self::deposit_event(Event::SettlementRetried(id));
+            Ok(())
+        }
+
+        /// Give up on a failed settlement which can't succeed: the winning bid goes back to the
+        /// winner and the nfts back to the seller. The winner and root can call this at any
+        /// time, the seller only once `SettlementGracePeriod` blocks passed since the failure.
+        /// - `id` is the auction whose settlement failed
+        #[pallet::weight(1_000)]
+        #[transactional]
+        fn abandon_settlement(origin: OriginFor<T>, id: T::AuctionId) -> DispatchResult {
+            let (listing, winner, failed_at) =
+                FailedSettlements::<T>::get(id).ok_or(Error::<T>::SettlementNotFound)?;
+            if let Ok(who) = ensure_signed(origin.clone()) {
+                let is_winner = winner.as_ref().map_or(false, |(winner, _)| *winner == who);
+                if !is_winner {
+                    ensure!(who == listing.seller, Error::<T>::NotSettlementParty);
+                    // the winning bid is held for the winner, who should get a chance to retry
+                    // before the seller can call the sale off
+                    let now = frame_system::Pallet::<T>::block_number();
+                    ensure!(
+                        winner.is_none()
+                            || now >= failed_at.saturating_add(T::SettlementGracePeriod::get()),
+                        Error::<T>::GracePeriodNotOver
+                    );
+                }
+            } else {
+                ensure_root(origin)?;
+            }
+
+            FailedSettlements::<T>::remove(id);
+            if let Some((winner, price)) = winner {
+                T::MultiCurrency::unreserve(listing.currency_id, &winner, price);
+            }
+            Self::transfer_nfts(
+                &T::PalletId::get().into_account(),
+                &listing.seller,
+                &listing.nfts,
+            )?;
+
+            Self::deposit_event(Event::SettlementAbandoned(id));
+            Self::deposit_event(Event::NftReturned(id, listing.seller));
+            Ok(())
+        }
+
+        /// Change the cut of every settlement taken by the marketplace.
+        /// - `fee` is the new cut, at most `MaxMarketplaceFee`
+        #[pallet::weight(1_000)]
+        fn set_fee(origin: OriginFor<T>, fee: Permill) -> DispatchResult {
+            ensure_root(origin)?;
+            ensure!(fee <= T::MaxMarketplaceFee::get(), Error::<T>::FeeTooHigh);
+
+            Fee::<T>::put(fee);
+
+            Self::deposit_event(Event::FeeSet(fee));
+            Ok(())
+        }
+    }
+}
+
+impl<T: Config> Pallet<T> {
+    /// Start of the reveal phase and currency of sealed auction `id`.
+    fn sealed_phases(id: T::AuctionId) -> Result<(T::BlockNumber, CurrencyIdOf<T>), DispatchError> {
+        let listing = Auctions::<T>::get(id).ok_or(Error::<T>::AuctionNotFound)?;
+        match listing.kind {
+            AuctionKind::Sealed { reveal_start } => Ok((reveal_start, listing.currency_id)),
+            _ => Err(Error::<T>::NotSealedAuction.into()),
+        }
+    }
+
+    /// Close the sealed bids of auction `id`: slash the unrevealed ones, refund the losing
+    /// ones and leave the second highest price reserved from the winner.
+    fn close_sealed(
+        id: T::AuctionId,
+        listing: &AuctionListingOf<T>,
+    ) -> Option<(T::AccountId, BalanceOf<T>)> {
+        SealedBidCount::<T>::remove(id);
+        let currency_id = listing.currency_id;
+        let start_price = listing.start_price;
+
+        let mut revealed = Vec::new();
+        for (bidder, bid) in SealedBids::<T>::drain_prefix(id) {
+            match bid.revealed {
+                Some(amount) if amount >= start_price => {
+                    revealed.push((bidder, amount, bid.deposit))
+                }
+                Some(amount) => {
+                    T::MultiCurrency::unreserve(currency_id, &bidder, bid.deposit);
+                    Self::deposit_event(Event::BidRejected(id, bidder, amount));
+                }
+                None => {
+                    if T::MultiCurrency::repatriate_reserved(
+                        currency_id,
+                        &bidder,
+                        &T::TreasuryAccount::get(),
+                        bid.deposit,
+                        BalanceStatus::Free,
+                    )
+                    .is_err()
+                    {
+                        // the deposit is burnt rather than left reserved forever
+                        T::MultiCurrency::slash_reserved(currency_id, &bidder, bid.deposit);
+                    }
+                    Self::deposit_event(Event::SealedBidSlashed(id, bidder, bid.deposit));
+                }
+            }
+        }
+
+        revealed.sort_by(|a, b| b.1.cmp(&a.1));
+        let price = revealed.get(1).map_or(start_price, |second| second.1);
+
+        let mut winner = None;
+        for (index, (bidder, _, deposit)) in revealed.into_iter().enumerate() {
+            if index == 0 {
+                T::MultiCurrency::unreserve(currency_id, &bidder, deposit.saturating_sub(price));
+                winner = Some((bidder, price));
+            } else {
+                T::MultiCurrency::unreserve(currency_id, &bidder, deposit);
+            }
+        }
+        winner
+    }
+
+    /// Price a dutch auction can be bought at right now, `None` for other auctions.
+    pub fn current_price(id: T::AuctionId) -> Option<BalanceOf<T>> {
+        let listing = Auctions::<T>::get(id)?;
+        let (floor_price, step) = match listing.kind {
+            AuctionKind::Dutch { floor_price, step } => (floor_price, step),
+            AuctionKind::English | AuctionKind::Sealed { .. } => return None,
+        };
+        let auction = orml_auction::Pallet::<T>::auction_info(id)?;
+        let end = auction.end?;
+
+        let now = frame_system::Pallet::<T>::block_number();
+        let duration = end.saturating_sub(auction.start);
+        if duration.is_zero() || now >= end {
+            return Some(floor_price);
+        }
+        let elapsed = now.saturating_sub(auction.start);
+        // round down to the last step the price dropped at
+        let elapsed = elapsed.saturating_sub(elapsed % step);
+
+        let range = listing.start_price.saturating_sub(floor_price);
+        let drop = multiply_by_rational(
+            range.saturated_into::<u128>(),
+            elapsed.saturated_into::<u128>(),
+            duration.saturated_into::<u128>(),
+        )
+        .map(|drop| drop.saturated_into::<BalanceOf<T>>())
+        .unwrap_or(range);
+
+        Some(listing.start_price.saturating_sub(drop))
+    }
+
+    /// Pay `price` from `buyer` to the seller of `listing`, minus the marketplace fee and
+    /// the royalties of its nft classes. Royalties are taken from what is left after the fee,
+    /// every nft of the lot accounting for an equal share of it, so the buyer never pays more
+    /// than `price`. A fee or royalty too small to open an account for its recipient is left
+    /// to the seller instead of being lost as dust or failing the settlement.
+    fn pay_out(
+        id: T::AuctionId,
+        buyer: &T::AccountId,
+        listing: &AuctionListingOf<T>,
+        price: BalanceOf<T>,
+    ) -> DispatchResult {
+        let mut seller_amount = price;
+
+        let fee = Fee::<T>::get() * price;
+        if !fee.is_zero()
+            && !Self::below_minimum(listing.currency_id, &T::TreasuryAccount::get(), fee)
+        {
+            T::MultiCurrency::transfer(
+                listing.currency_id,
+                buyer,
+                &T::TreasuryAccount::get(),
+                fee,
+            )?;
+            seller_amount = seller_amount.saturating_sub(fee);
+            Self::deposit_event(Event::FeeCharged(id, fee));
+        }
+
+        let share = seller_amount / BalanceOf::<T>::from(listing.nfts.len() as u32);
+        let mut royalties: BalanceOf<T> = Zero::zero();
+        for (class_id, _) in listing.nfts.iter() {
+            if let Some((recipient, rate)) = kodadot_nft::Pallet::<T>::royalty(*class_id) {
+                let amount = rate * share;
+                if amount.is_zero() || Self::below_minimum(listing.currency_id, &recipient, amount)
+                {
+                    continue;
+                }
+                T::MultiCurrency::transfer(listing.currency_id, buyer, &recipient, amount)?;
+                royalties = royalties.saturating_add(amount);
+                Self::deposit_event(Event::RoyaltyPaid(id, recipient, amount));
+            }
+        }
+        seller_amount = seller_amount.saturating_sub(royalties);
+        T::MultiCurrency::transfer(listing.currency_id, buyer, &listing.seller, seller_amount)?;
+
+        Self::deposit_event(Event::Settled(
+            id,
+            listing.seller.clone(),
+            seller_amount,
+            royalties,
+        ));
+        Ok(())
+    }
+
+    /// Whether paying `amount` to `to` would leave it below the existential deposit of
+    /// `currency_id`.
+    fn below_minimum(
+        currency_id: CurrencyIdOf<T>,
+        to: &T::AccountId,
+        amount: BalanceOf<T>,
+    ) -> bool {
+        T::MultiCurrency::total_balance(currency_id, to).saturating_add(amount)
+            < T::MultiCurrency::minimum_balance(currency_id)
+    }
+
+    /// Pay the seller of `listing` and hand the nfts over to the winner, or back to the
+    /// seller if there is none. Either everything happens or nothing does.
+    #[transactional]
+    fn settle(
+        id: T::AuctionId,
+        listing: &AuctionListingOf<T>,
+        winner: &Option<(T::AccountId, BalanceOf<T>)>,
+    ) -> DispatchResult {
+        let dest = match winner {
+            Some((buyer, price)) => {
+                ensure!(
+                    T::MultiCurrency::unreserve(listing.currency_id, buyer, *price).is_zero(),
+                    Error::<T>::WinningBidNotReserved
+                );
+                Self::pay_out(id, buyer, listing, *price)?;
+                Self::deposit_event(Event::AuctionWon(id, buyer.clone(), *price));
+                buyer
+            }
+            None => {
+                Self::deposit_event(Event::AuctionExpiredUnsold(id));
+                &listing.seller
+            }
+        };
+
+        Self::transfer_nfts(&T::PalletId::get().into_account(), dest, &listing.nfts)?;
+
+        if winner.is_none() {
+            Self::deposit_event(Event::NftReturned(id, listing.seller.clone()));
+        }
+        Ok(())
+    }
+
+    /// Transfer all `nfts` from `from` to `to`.
+    fn transfer_nfts(
+        from: &T::AccountId,
+        to: &T::AccountId,
+        nfts: &[(T::ClassId, T::TokenId)],
+    ) -> DispatchResult {
+        for nft in nfts.iter() {
+            ensure!(
+                <kodadot_nft::Pallet<T> as NFT<T::AccountId>>::transfer(from, to, *nft).is_ok(),
+                Error::<T>::FailedNftTransfer
+            );
+        }
+        Ok(())
+    }
+
+    /// Push the end of auction `id` out if `now` falls within the extension window,
+    /// without going over the maximum total extension.
+    fn extend_if_sniped(
+        now: T::BlockNumber,
+        id: T::AuctionId,
+        extended: T::BlockNumber,
+    ) -> Change<Option<T::BlockNumber>> {
+        let end = match orml_auction::Pallet::<T>::auction_info(id).and_then(|info| info.end) {
+            Some(end) => end,
+            None => return Change::NoChange,
+        };
+
+        let window = T::ExtensionWindow::get();
+        if end.saturating_sub(now) >= window {
+            return Change::NoChange;
+        }
+
+        let extension = window.min(T::MaxExtension::get().saturating_sub(extended));
+        if extension.is_zero() {
+            return Change::NoChange;
+        }
+
+        let new_end = end.saturating_add(extension);
+        let total = extended.saturating_add(extension);
+        Auctions::<T>::mutate(id, |listing| {
+            if let Some(listing) = listing {
+                listing.extended = total;
+            }
+        });
+
+        Self::deposit_event(Event::AuctionExtended(id, new_end, total));
+        Change::NewValue(Some(new_end))
     }
 }
 
@@ -120,57 +883,103 @@ impl<T: Config> AuctionHandler<T::AccountId, BalanceOf<T>, T::BlockNumber, T::Au
     for Pallet<T>
 {
     fn on_new_bid(
-        _now: T::BlockNumber,
+        now: T::BlockNumber,
         id: T::AuctionId,
         new_bid: (T::AccountId, BalanceOf<T>),
         last_bid: Option<(T::AccountId, BalanceOf<T>)>,
     ) -> OnNewBidResult<T::BlockNumber> {
-        let minimum_bid = Auctions::<T>::get(id).1;
-        if new_bid.1 < minimum_bid {
-            return OnNewBidResult {
-                accept_bid: false,
-                auction_end_change: Change::NoChange,
-            };
+        // a rejected bid fails the whole `bid` extrinsic, so nothing done here would persist
+        let reject = || OnNewBidResult {
+            accept_bid: false,
+            auction_end_change: Change::NoChange,
+        };
+
+        let listing = match Auctions::<T>::get(id) {
+            Some(listing) => listing,
+            None => return reject(),
+        };
+        if new_bid.0 == listing.seller || listing.kind != AuctionKind::English {
+            // sellers can't bid up their own nft, only english auctions take open bids
+            return reject();
+        }
+
+        let reaches_buy_now = |amount: &BalanceOf<T>| {
+            listing
+                .buy_now_price
+                .map_or(false, |price| *amount >= price)
+        };
+        if last_bid
+            .as_ref()
+            .map_or(false, |(_, amount)| reaches_buy_now(amount))
+        {
+            // already bought, the auction ends this block
+            return reject();
         }
+        let buy_now = reaches_buy_now(&new_bid.1);
 
-        if T::Currency::reserve(&new_bid.0, new_bid.1).is_err() {
-            // failed to reserve new coins
+        let minimum_bid = match last_bid {
+            Some((_, ref amount)) => amount.saturating_add(T::MinimumBidIncrement::get() * *amount),
+            None => listing.start_price,
+        };
+        if new_bid.1 < minimum_bid && !buy_now {
+            return reject();
+        }
+
+        match last_bid {
+            // the highest bidder raises their own bid, only reserve the difference
+            Some((ref bidder, amount)) if *bidder == new_bid.0 => {
+                if T::MultiCurrency::reserve(
+                    listing.currency_id,
+                    &new_bid.0,
+                    new_bid.1.saturating_sub(amount),
+                )
+                .is_err()
+                {
+                    return reject();
+                }
+            }
+            _ => {
+                if T::MultiCurrency::reserve(listing.currency_id, &new_bid.0, new_bid.1).is_err() {
+                    // failed to reserve new coins
+                    return reject();
+                }
+                if let Some((bidder, amount)) = last_bid {
+                    T::MultiCurrency::unreserve(listing.currency_id, &bidder, amount);
+                }
+            }
+        }
+
+        Self::deposit_event(Event::BidPlaced(id, new_bid.0.clone(), new_bid.1));
+
+        if buy_now {
+            Self::deposit_event(Event::BoughtNow(id, new_bid.0, new_bid.1));
+            // ending now lets orml_auction settle it through `on_auction_ended` this block
             return OnNewBidResult {
-                accept_bid: false,
-                auction_end_change: Change::NoChange,
+                accept_bid: true,
+                auction_end_change: Change::NewValue(Some(now)),
             };
         }
-        if let Some(bid) = last_bid {
-            T::Currency::unreserve(&bid.0, bid.1);
-        }
 
         OnNewBidResult {
-            accept_bid: false,
-            auction_end_change: Change::NoChange,
+            accept_bid: true,
+            auction_end_change: Self::extend_if_sniped(now, id, listing.extended),
         }
     }
 
     fn on_auction_ended(id: T::AuctionId, winner: Option<(T::AccountId, BalanceOf<T>)>) {
-        let meta = Auctions::<T>::take(id);
-        let dest = match winner.clone() {
-            Some((acc, _)) => acc,
-            None => meta.clone().0,
+        let listing = match Auctions::<T>::take(id) {
+            Some(listing) => listing,
+            None => return,
+        };
+        let winner = match listing.kind {
+            AuctionKind::Sealed { .. } => Self::close_sealed(id, &listing),
+            _ => winner,
         };
 
-        if let Some(details) = winner {
-            T::Currency::unreserve(&details.0, details.1);
-            T::Currency::transfer(
-                &details.0,
-                &meta.0,
-                details.1,
-                frame_support::traits::ExistenceRequirement::AllowDeath,
-            );
+        if let Err(error) = Self::settle(id, &listing, &winner) {
+            let now = frame_system::Pallet::<T>::block_number();
+            FailedSettlements::<T>::insert(id, (listing, winner, now));
+            Self::deposit_event(Event::SettlementFailed(id, error));
         }
-
-        orml_nft::Pallet::<T>::transfer(
-            &T::PalletId::get().into_account(),
-            &dest,
-            (meta.2, meta.3),
-        );
     }
 }
@@ -0,0 +1,222 @@
+//! Test runtime for the auction pallet.
+
+use crate as kodadot_auction;
+use frame_support::{
+    construct_runtime, parameter_types,
+    traits::{OnFinalize, OnInitialize},
+    PalletId,
+};
+use orml_traits::{parameter_type_with_key, MultiCurrency};
+use sp_core::H256;
+use sp_runtime::{
+    testing::Header,
+    traits::{BlakeTwo256, IdentityLookup, Zero},
+    Permill,
+};
+
+pub type AccountId = u64;
+pub type Balance = u128;
+pub type BlockNumber = u64;
+pub type CurrencyId = u32;
+pub type ClassId = u32;
+pub type TokenId = u64;
+
+pub const ALICE: AccountId = 1;
+pub const BOB: AccountId = 2;
+pub const CHARLIE: AccountId = 3;
+pub const DAVE: AccountId = 4;
+pub const EVE: AccountId = 5;
+pub const TREASURY: AccountId = 100;
+
+pub const DOT: CurrencyId = 1;
+/// Only currency with an existential deposit.
+pub const KSM: CurrencyId = 2;
+pub const INITIAL_BALANCE: Balance = 1_000;
+
+parameter_types! {
+    pub const BlockHashCount: u64 = 250;
+}
+
+impl frame_system::Config for Runtime {
+    type BaseCallFilter = ();
+    type BlockWeights = ();
+    type BlockLength = ();
+    type Origin = Origin;
+    type Call = Call;
+    type Index = u64;
+    type BlockNumber = BlockNumber;
+    type Hash = H256;
+    type Hashing = BlakeTwo256;
+    type AccountId = AccountId;
+    type Lookup = IdentityLookup<Self::AccountId>;
+    type Header = Header;
+    type Event = Event;
+    type BlockHashCount = BlockHashCount;
+    type DbWeight = ();
+    type Version = ();
+    type PalletInfo = PalletInfo;
+    type AccountData = pallet_balances::AccountData<Balance>;
+    type OnNewAccount = ();
+    type OnKilledAccount = ();
+    type SystemWeightInfo = ();
+    type SS58Prefix = ();
+    type OnSetCode = ();
+}
+
+parameter_types! {
+    pub const ExistentialDeposit: Balance = 1;
+}
+
+impl pallet_balances::Config for Runtime {
+    type MaxLocks = ();
+    type Balance = Balance;
+    type Event = Event;
+    type DustRemoval = ();
+    type ExistentialDeposit = ExistentialDeposit;
+    type AccountStore = System;
+    type WeightInfo = ();
+}
+
+parameter_type_with_key! {
+    pub ExistentialDeposits: |currency_id: CurrencyId| -> Balance {
+        match *currency_id {
+            KSM => 20,
+            _ => Zero::zero(),
+        }
+    };
+}
+
+impl orml_tokens::Config for Runtime {
+    type Event = Event;
+    type Balance = Balance;
+    type Amount = i128;
+    type CurrencyId = CurrencyId;
+    type WeightInfo = ();
+    type ExistentialDeposits = ExistentialDeposits;
+    type OnDust = orml_tokens::TransferDust<Runtime, TreasuryAccount>;
+}
+
+impl orml_auction::Config for Runtime {
+    type Event = Event;
+    type Balance = Balance;
+    type AuctionId = u32;
+    type Handler = AuctionManager;
+    type WeightInfo = ();
+}
+
+impl orml_nft::Config for Runtime {
+    type ClassId = ClassId;
+    type TokenId = TokenId;
+    type ClassData = kodadot_nft::ClassData<AccountId>;
+    type TokenData = kodadot_nft::TokenData;
+}
+
+parameter_types! {
+    pub const ClassDeposit: Balance = 1;
+    pub const TokenDeposit: Balance = 1;
+    pub const DepositPerByte: Balance = 1;
+    pub const MaxMetadataLength: u32 = 32;
+    pub const MaxRoyalty: Permill = Permill::from_percent(50);
+    pub const AttributeDeposit: Balance = 1;
+    pub const MaxAttributeKeyLength: u32 = 32;
+    pub const MaxAttributeValueLength: u32 = 32;
+    pub const MaxAttributes: u32 = 4;
+}
+
+impl kodadot_nft::Config for Runtime {
+    type Event = Event;
+    type Currency = Balances;
+    type ClassDeposit = ClassDeposit;
+    type TokenDeposit = TokenDeposit;
+    type DepositPerByte = DepositPerByte;
+    type MaxMetadataLength = MaxMetadataLength;
+    type MaxRoyalty = MaxRoyalty;
+    type AttributeDeposit = AttributeDeposit;
+    type MaxAttributeKeyLength = MaxAttributeKeyLength;
+    type MaxAttributeValueLength = MaxAttributeValueLength;
+    type MaxAttributes = MaxAttributes;
+}
+
+parameter_types! {
+    pub const AuctionPalletId: PalletId = PalletId(*b"kod/auct");
+    pub const TreasuryAccount: AccountId = TREASURY;
+    pub const MinimumBidIncrement: Permill = Permill::from_percent(5);
+    pub const ExtensionWindow: BlockNumber = 5;
+    pub const MaxExtension: BlockNumber = 20;
+    pub const MaxSealedBids: u32 = 10;
+    pub const MarketplaceFee: Permill = Permill::from_percent(10);
+    pub const MaxMarketplaceFee: Permill = Permill::from_percent(20);
+    pub const MaxBundleSize: u32 = 3;
+    pub const MinAuctionDuration: BlockNumber = 10;
+    pub const MaxAuctionDuration: BlockNumber = 1_000;
+    pub const SettlementGracePeriod: BlockNumber = 10;
+}
+
+impl kodadot_auction::Config for Runtime {
+    type Event = Event;
+    type MultiCurrency = Tokens;
+    type TreasuryAccount = TreasuryAccount;
+    type PalletId = AuctionPalletId;
+    type MinimumBidIncrement = MinimumBidIncrement;
+    type ExtensionWindow = ExtensionWindow;
+    type MaxExtension = MaxExtension;
+    type MaxSealedBids = MaxSealedBids;
+    type MarketplaceFee = MarketplaceFee;
+    type MaxMarketplaceFee = MaxMarketplaceFee;
+    type MaxBundleSize = MaxBundleSize;
+    type MinAuctionDuration = MinAuctionDuration;
+    type MaxAuctionDuration = MaxAuctionDuration;
+    type SettlementGracePeriod = SettlementGracePeriod;
+}
+
+type UncheckedExtrinsic = frame_system::mocking::MockUncheckedExtrinsic<Runtime>;
+type Block = frame_system::mocking::MockBlock<Runtime>;
+
+construct_runtime!(
+    pub enum Runtime where
+        Block = Block,
+        NodeBlock = Block,
+        UncheckedExtrinsic = UncheckedExtrinsic,
+    {
+        System: frame_system::{Pallet, Call, Config, Storage, Event<T>},
+        Balances: pallet_balances::{Pallet, Call, Storage, Config<T>, Event<T>},
+        Tokens: orml_tokens::{Pallet, Storage, Event<T>},
+        OrmlAuction: orml_auction::{Pallet, Storage, Call, Event<T>},
+        OrmlNFT: orml_nft::{Pallet, Storage, Config<T>},
+        NFT: kodadot_nft::{Pallet, Call, Storage, Event<T>},
+        AuctionManager: kodadot_auction::{Pallet, Call, Storage, Event<T>},
+    }
+);
+
+pub fn new_test_ext() -> sp_io::TestExternalities {
+    let mut storage = frame_system::GenesisConfig::default()
+        .build_storage::<Runtime>()
+        .unwrap();
+    pallet_balances::GenesisConfig::<Runtime> {
+        balances: vec![
+            (ALICE, INITIAL_BALANCE),
+            (BOB, INITIAL_BALANCE),
+            (CHARLIE, INITIAL_BALANCE),
+        ],
+    }
+    .assimilate_storage(&mut storage)
+    .unwrap();
+
+    let mut ext = sp_io::TestExternalities::new(storage);
+    ext.execute_with(|| {
+        System::set_block_number(1);
+        for who in vec![ALICE, BOB, CHARLIE, DAVE, EVE] {
+            Tokens::deposit(DOT, &who, INITIAL_BALANCE).unwrap();
+        }
+    });
+    ext
+}
+
+/// Run blocks up to `n`, ending the auctions scheduled along the way.
+pub fn run_to_block(n: BlockNumber) {
+    while System::block_number() < n {
+        OrmlAuction::on_finalize(System::block_number());
+        System::set_block_number(System::block_number() + 1);
+        OrmlAuction::on_initialize(System::block_number());
+    }
+}
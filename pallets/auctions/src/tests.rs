@@ -0,0 +1,992 @@
+//! Unit tests for the auction pallet.
+
+use crate::{mock::*, AuctionKind, Error, FailedSettlements, SealedBids};
+use codec::{Decode, Encode};
+use frame_support::{
+    assert_noop, assert_ok,
+    dispatch::DispatchResultWithPostInfo,
+    traits::{Get, UnfilteredDispatchable},
+    BoundedVec,
+};
+use orml_traits::{Auction, MultiCurrency, MultiReservableCurrency};
+use sp_core::H256;
+use sp_runtime::{
+    traits::{AccountIdConversion, BlakeTwo256, Hash},
+    Permill,
+};
+use sp_std::convert::TryInto;
+
+type AuctionCall = crate::Call<Runtime>;
+type AuctionEvent = crate::Event<Runtime>;
+
+const AUCTION: u32 = 0;
+const START: BlockNumber = 1;
+const REVEAL_START: BlockNumber = 11;
+const END: BlockNumber = 21;
+const DUTCH: AuctionKind<Balance, BlockNumber> = AuctionKind::Dutch {
+    floor_price: 20,
+    step: 5,
+};
+
+fn dispatch(who: AccountId, call: AuctionCall) -> DispatchResultWithPostInfo {
+    call.dispatch_bypass_filter(Origin::signed(who))
+}
+
+fn bid(who: AccountId, amount: Balance) -> DispatchResultWithPostInfo {
+    orml_auction::Call::<Runtime>::bid(AUCTION, amount).dispatch_bypass_filter(Origin::signed(who))
+}
+
+fn has_event(event: AuctionEvent) -> bool {
+    let event: Event = event.into();
+    System::events().iter().any(|record| record.event == event)
+}
+
+fn commitment(who: AccountId, amount: Balance, salt: [u8; 32]) -> H256 {
+    BlakeTwo256::hash_of(&(AUCTION, who, amount, salt))
+}
+
+/// Create a class with `royalty` and mint its first nft for `ALICE`.
+fn mint_nft(royalty: Option<(AccountId, Permill)>) -> (ClassId, TokenId) {
+    let class_id = OrmlNFT::next_class_id();
+    assert_ok!(NFT::create_class(
+        Origin::signed(ALICE),
+        Vec::new(),
+        royalty,
+        None
+    ));
+    assert_ok!(NFT::mint(
+        Origin::signed(ALICE),
+        ALICE,
+        class_id,
+        Vec::new(),
+        1
+    ));
+    (class_id, 0)
+}
+
+/// List `nfts` of `ALICE` from `START` to `END`, starting at 100 DOT.
+fn list(
+    nfts: Vec<(ClassId, TokenId)>,
+    kind: AuctionKind<Balance, BlockNumber>,
+    buy_now_price: Option<Balance>,
+) -> DispatchResultWithPostInfo {
+    dispatch(
+        ALICE,
+        AuctionCall::list(
+            nfts.try_into().unwrap(),
+            100,
+            START,
+            END,
+            buy_now_price,
+            kind,
+            DOT,
+        ),
+    )
+}
+
+/// Mint a nft for `ALICE` and list it in an english auction starting at 100 DOT.
+fn list_english() {
+    let nft = mint_nft(None);
+    assert_ok!(list(vec![nft], AuctionKind::English, None));
+}
+
+/// Mint a nft for `ALICE` and list it in a sealed auction starting at 100 DOT.
+fn list_sealed() {
+    list_sealed_with_royalty(None);
+}
+
+fn list_sealed_with_royalty(royalty: Option<(AccountId, Permill)>) {
+    let nft = mint_nft(royalty);
+    assert_ok!(list(
+        vec![nft],
+        AuctionKind::Sealed {
+            reveal_start: REVEAL_START
+        },
+        None
+    ));
+}
+
+fn commit(who: AccountId, amount: Balance, deposit: Balance) -> DispatchResultWithPostInfo {
+    dispatch(
+        who,
+        AuctionCall::commit_bid(AUCTION, commitment(who, amount, [who as u8; 32]), deposit),
+    )
+}
+
+fn reveal(who: AccountId, amount: Balance) -> DispatchResultWithPostInfo {
+    dispatch(
+        who,
+        AuctionCall::reveal_bid(AUCTION, amount, [who as u8; 32]),
+    )
+}
+
+#[test]
+fn english_bids_start_at_the_start_price() {
+    new_test_ext().execute_with(|| {
+        list_english();
+
+        assert_noop!(bid(BOB, 99), orml_auction::Error::<Runtime>::BidNotAccepted);
+        assert_ok!(bid(BOB, 100));
+
+        assert_eq!(Tokens::reserved_balance(DOT, &BOB), 100);
+        assert!(has_event(AuctionEvent::BidPlaced(AUCTION, BOB, 100)));
+    });
+}
+
+#[test]
+fn english_bids_add_the_minimum_increment() {
+    new_test_ext().execute_with(|| {
+        list_english();
+        assert_ok!(bid(BOB, 100));
+
+        assert_noop!(
+            bid(CHARLIE, 104),
+            orml_auction::Error::<Runtime>::BidNotAccepted
+        );
+        assert_ok!(bid(CHARLIE, 105));
+    });
+}
+
+#[test]
+fn seller_cannot_bid_on_own_auction() {
+    new_test_ext().execute_with(|| {
+        list_english();
+
+        assert_noop!(
+            bid(ALICE, 100),
+            orml_auction::Error::<Runtime>::BidNotAccepted
+        );
+    });
+}
+
+#[test]
+fn outbid_bidder_gets_the_reserve_back() {
+    new_test_ext().execute_with(|| {
+        list_english();
+        assert_ok!(bid(BOB, 100));
+        assert_ok!(bid(CHARLIE, 105));
+
+        assert_eq!(Tokens::reserved_balance(DOT, &BOB), 0);
+        assert_eq!(Tokens::free_balance(DOT, &BOB), INITIAL_BALANCE);
+        assert_eq!(Tokens::reserved_balance(DOT, &CHARLIE), 105);
+        assert!(has_event(AuctionEvent::BidPlaced(AUCTION, CHARLIE, 105)));
+    });
+}
+
+#[test]
+fn raising_own_bid_reserves_the_difference() {
+    new_test_ext().execute_with(|| {
+        list_english();
+        assert_ok!(bid(BOB, 100));
+        assert_ok!(bid(BOB, 200));
+
+        assert_eq!(Tokens::reserved_balance(DOT, &BOB), 200);
+        assert_eq!(Tokens::free_balance(DOT, &BOB), INITIAL_BALANCE - 200);
+    });
+}
+
+#[test]
+fn bids_which_cannot_be_reserved_are_rejected() {
+    new_test_ext().execute_with(|| {
+        list_english();
+
+        assert_noop!(
+            bid(BOB, INITIAL_BALANCE + 1),
+            orml_auction::Error::<Runtime>::BidNotAccepted
+        );
+    });
+}
+
+#[test]
+fn english_auction_is_won_by_the_highest_bid() {
+    new_test_ext().execute_with(|| {
+        list_english();
+        assert_ok!(bid(BOB, 100));
+        assert_ok!(bid(CHARLIE, 200));
+
+        run_to_block(END + 1);
+
+        assert_eq!(OrmlNFT::tokens(0, 0).unwrap().owner, CHARLIE);
+        assert_eq!(Tokens::free_balance(DOT, &CHARLIE), INITIAL_BALANCE - 200);
+        assert_eq!(Tokens::reserved_balance(DOT, &CHARLIE), 0);
+        assert_eq!(Tokens::free_balance(DOT, &BOB), INITIAL_BALANCE);
+        // the seller gets the price minus the 10% marketplace fee
+        assert_eq!(Tokens::free_balance(DOT, &ALICE), INITIAL_BALANCE + 180);
+        assert_eq!(Tokens::free_balance(DOT, &TREASURY), 20);
+        assert!(has_event(AuctionEvent::AuctionWon(AUCTION, CHARLIE, 200)));
+        assert!(has_event(AuctionEvent::Settled(AUCTION, ALICE, 180, 0)));
+        assert!(AuctionManager::auctions(AUCTION).is_none());
+    });
+}
+
+#[test]
+fn english_auction_without_bids_returns_the_nft() {
+    new_test_ext().execute_with(|| {
+        list_english();
+
+        run_to_block(END + 1);
+
+        assert_eq!(OrmlNFT::tokens(0, 0).unwrap().owner, ALICE);
+        assert!(has_event(AuctionEvent::AuctionExpiredUnsold(AUCTION)));
+        assert!(has_event(AuctionEvent::NftReturned(AUCTION, ALICE)));
+        assert!(AuctionManager::auctions(AUCTION).is_none());
+    });
+}
+
+#[test]
+fn early_bids_do_not_extend_the_auction() {
+    new_test_ext().execute_with(|| {
+        list_english();
+        run_to_block(END - ExtensionWindow::get());
+        assert_ok!(bid(BOB, 100));
+
+        assert_eq!(OrmlAuction::auction_info(AUCTION).unwrap().end, Some(END));
+        assert_eq!(AuctionManager::auctions(AUCTION).unwrap().extended, 0);
+    });
+}
+
+#[test]
+fn late_bids_extend_the_auction() {
+    new_test_ext().execute_with(|| {
+        list_english();
+        run_to_block(END - ExtensionWindow::get() + 1);
+        assert_ok!(bid(BOB, 100));
+
+        assert_eq!(
+            OrmlAuction::auction_info(AUCTION).unwrap().end,
+            Some(END + 5)
+        );
+        assert_eq!(AuctionManager::auctions(AUCTION).unwrap().extended, 5);
+        assert!(has_event(AuctionEvent::AuctionExtended(
+            AUCTION,
+            END + 5,
+            5
+        )));
+
+        // the auction doesn't end at its original end anymore
+        run_to_block(END + 1);
+        assert_eq!(
+            OrmlNFT::tokens(0, 0).unwrap().owner,
+            AuctionPalletId::get().into_account()
+        );
+        run_to_block(END + 6);
+        assert_eq!(OrmlNFT::tokens(0, 0).unwrap().owner, BOB);
+    });
+}
+
+#[test]
+fn extensions_are_capped() {
+    new_test_ext().execute_with(|| {
+        list_english();
+        // every bid lands 4 blocks before the current end
+        for (round, (who, amount)) in vec![(BOB, 100), (CHARLIE, 110), (BOB, 120), (CHARLIE, 130)]
+            .into_iter()
+            .enumerate()
+        {
+            run_to_block(END - 4 + 5 * round as BlockNumber);
+            assert_ok!(bid(who, amount));
+        }
+        assert_eq!(
+            OrmlAuction::auction_info(AUCTION).unwrap().end,
+            Some(END + 20)
+        );
+        assert_eq!(
+            AuctionManager::auctions(AUCTION).unwrap().extended,
+            MaxExtension::get()
+        );
+
+        run_to_block(END + 16);
+        assert_ok!(bid(BOB, 140));
+        assert_eq!(
+            OrmlAuction::auction_info(AUCTION).unwrap().end,
+            Some(END + 20)
+        );
+
+        run_to_block(END + 21);
+        assert_eq!(OrmlNFT::tokens(0, 0).unwrap().owner, BOB);
+        assert_eq!(Tokens::free_balance(DOT, &BOB), INITIAL_BALANCE - 140);
+    });
+}
+
+#[test]
+fn seller_can_cancel_before_any_bid() {
+    new_test_ext().execute_with(|| {
+        list_english();
+
+        assert_noop!(
+            dispatch(BOB, AuctionCall::cancel(AUCTION)),
+            Error::<Runtime>::NotAuctionOwner
+        );
+        assert_ok!(dispatch(ALICE, AuctionCall::cancel(AUCTION)));
+
+        assert_eq!(OrmlNFT::tokens(0, 0).unwrap().owner, ALICE);
+        assert!(AuctionManager::auctions(AUCTION).is_none());
+        assert!(OrmlAuction::auction_info(AUCTION).is_none());
+        assert!(has_event(AuctionEvent::ListingCancelled(AUCTION)));
+        assert_noop!(
+            dispatch(ALICE, AuctionCall::cancel(AUCTION)),
+            Error::<Runtime>::AuctionNotFound
+        );
+    });
+}
+
+#[test]
+fn auction_with_bids_cannot_be_cancelled() {
+    new_test_ext().execute_with(|| {
+        list_english();
+        assert_ok!(bid(BOB, 100));
+
+        assert_noop!(
+            dispatch(ALICE, AuctionCall::cancel(AUCTION)),
+            Error::<Runtime>::AuctionHasBids
+        );
+    });
+}
+
+#[test]
+fn auction_with_sealed_bids_cannot_be_cancelled() {
+    new_test_ext().execute_with(|| {
+        list_sealed();
+        assert_ok!(commit(BOB, 100, 100));
+
+        assert_noop!(
+            dispatch(ALICE, AuctionCall::cancel(AUCTION)),
+            Error::<Runtime>::AuctionHasBids
+        );
+    });
+}
+
+#[test]
+fn buy_now_price_cannot_be_below_the_start_price() {
+    new_test_ext().execute_with(|| {
+        let nft = mint_nft(None);
+
+        assert_noop!(
+            list(vec![nft], AuctionKind::English, Some(99)),
+            Error::<Runtime>::InvalidBuyNowPrice
+        );
+    });
+}
+
+#[test]
+fn bid_at_the_buy_now_price_settles_in_the_same_block() {
+    new_test_ext().execute_with(|| {
+        let nft = mint_nft(None);
+        assert_ok!(list(vec![nft], AuctionKind::English, Some(500)));
+        assert_ok!(bid(BOB, 500));
+
+        let now = System::block_number();
+        assert!(has_event(AuctionEvent::BoughtNow(AUCTION, BOB, 500)));
+        assert_eq!(OrmlAuction::auction_info(AUCTION).unwrap().end, Some(now));
+        assert!(bid(CHARLIE, 600).is_err());
+
+        run_to_block(now + 1);
+        assert_eq!(OrmlNFT::tokens(0, 0).unwrap().owner, BOB);
+        assert_eq!(Tokens::free_balance(DOT, &BOB), INITIAL_BALANCE - 500);
+        assert_eq!(Tokens::free_balance(DOT, &ALICE), INITIAL_BALANCE + 450);
+    });
+}
+
+#[test]
+fn buy_now_price_skips_the_minimum_increment() {
+    new_test_ext().execute_with(|| {
+        let nft = mint_nft(None);
+        assert_ok!(list(vec![nft], AuctionKind::English, Some(500)));
+        assert_ok!(bid(BOB, 480));
+        assert_ok!(bid(CHARLIE, 500));
+
+        run_to_block(System::block_number() + 1);
+        assert_eq!(OrmlNFT::tokens(0, 0).unwrap().owner, CHARLIE);
+        assert_eq!(Tokens::free_balance(DOT, &BOB), INITIAL_BALANCE);
+    });
+}
+
+#[test]
+fn bids_below_the_buy_now_price_keep_the_auction_running() {
+    new_test_ext().execute_with(|| {
+        let nft = mint_nft(None);
+        assert_ok!(list(vec![nft], AuctionKind::English, Some(500)));
+        assert_ok!(bid(BOB, 400));
+
+        assert_eq!(OrmlAuction::auction_info(AUCTION).unwrap().end, Some(END));
+        assert!(!has_event(AuctionEvent::BoughtNow(AUCTION, BOB, 400)));
+    });
+}
+
+/// Mint a nft for `ALICE` and list it in a dutch auction falling from 100 to 20 DOT.
+fn list_dutch() {
+    let nft = mint_nft(None);
+    assert_ok!(list(vec![nft], DUTCH, None));
+}
+
+#[test]
+fn dutch_price_falls_by_steps() {
+    new_test_ext().execute_with(|| {
+        list_dutch();
+        assert_eq!(AuctionManager::current_price(AUCTION), Some(100));
+
+        run_to_block(START + 5);
+        assert_eq!(AuctionManager::current_price(AUCTION), Some(80));
+        // between two steps
+        run_to_block(START + 7);
+        assert_eq!(AuctionManager::current_price(AUCTION), Some(80));
+        run_to_block(START + 10);
+        assert_eq!(AuctionManager::current_price(AUCTION), Some(60));
+        run_to_block(END);
+        assert_eq!(AuctionManager::current_price(AUCTION), Some(20));
+    });
+}
+
+#[test]
+fn current_price_is_only_for_dutch_auctions() {
+    new_test_ext().execute_with(|| {
+        list_english();
+
+        assert_eq!(AuctionManager::current_price(AUCTION), None);
+    });
+}
+
+#[test]
+fn dutch_auction_is_bought_at_the_current_price() {
+    new_test_ext().execute_with(|| {
+        list_dutch();
+        run_to_block(START + 5);
+
+        assert_noop!(
+            dispatch(BOB, AuctionCall::buy(AUCTION, 79)),
+            Error::<Runtime>::PriceAboveLimit
+        );
+        assert_ok!(dispatch(BOB, AuctionCall::buy(AUCTION, 90)));
+
+        assert_eq!(OrmlNFT::tokens(0, 0).unwrap().owner, BOB);
+        assert_eq!(Tokens::free_balance(DOT, &BOB), INITIAL_BALANCE - 80);
+        assert_eq!(Tokens::reserved_balance(DOT, &BOB), 0);
+        assert_eq!(Tokens::free_balance(DOT, &ALICE), INITIAL_BALANCE + 72);
+        assert!(has_event(AuctionEvent::BoughtNow(AUCTION, BOB, 80)));
+        assert!(AuctionManager::auctions(AUCTION).is_none());
+        assert!(OrmlAuction::auction_info(AUCTION).is_none());
+    });
+}
+
+#[test]
+fn dutch_auction_cannot_be_bought_by_its_seller() {
+    new_test_ext().execute_with(|| {
+        list_dutch();
+
+        assert_noop!(
+            dispatch(ALICE, AuctionCall::buy(AUCTION, 100)),
+            Error::<Runtime>::CannotBuyOwnAuction
+        );
+    });
+}
+
+#[test]
+fn dutch_auction_cannot_be_bought_before_it_starts() {
+    new_test_ext().execute_with(|| {
+        let nft = mint_nft(None);
+        assert_ok!(dispatch(
+            ALICE,
+            AuctionCall::list(
+                vec![nft].try_into().unwrap(),
+                100,
+                START + 5,
+                END + 5,
+                None,
+                DUTCH,
+                DOT
+            )
+        ));
+
+        assert_noop!(
+            dispatch(BOB, AuctionCall::buy(AUCTION, 100)),
+            Error::<Runtime>::AuctionNotStarted
+        );
+    });
+}
+
+#[test]
+fn only_dutch_auctions_can_be_bought() {
+    new_test_ext().execute_with(|| {
+        list_english();
+
+        assert_noop!(
+            dispatch(BOB, AuctionCall::buy(AUCTION, 100)),
+            Error::<Runtime>::NotDutchAuction
+        );
+    });
+}
+
+#[test]
+fn dutch_auction_rejects_bids() {
+    new_test_ext().execute_with(|| {
+        list_dutch();
+
+        assert_noop!(
+            bid(BOB, 100),
+            orml_auction::Error::<Runtime>::BidNotAccepted
+        );
+    });
+}
+
+#[test]
+fn dutch_auction_parameters_are_checked() {
+    new_test_ext().execute_with(|| {
+        let nft = mint_nft(None);
+
+        for (kind, buy_now_price) in vec![
+            (
+                AuctionKind::Dutch {
+                    floor_price: 101,
+                    step: 5,
+                },
+                None,
+            ),
+            (
+                AuctionKind::Dutch {
+                    floor_price: 20,
+                    step: 0,
+                },
+                None,
+            ),
+            (DUTCH, Some(200)),
+        ] {
+            assert_noop!(
+                list(vec![nft], kind, buy_now_price),
+                Error::<Runtime>::InvalidDutchAuction
+            );
+        }
+    });
+}
+
+#[test]
+fn auction_schedule_is_checked() {
+    new_test_ext().execute_with(|| {
+        let nft = mint_nft(None);
+        run_to_block(5);
+        let list_between = |start, end| {
+            dispatch(
+                ALICE,
+                AuctionCall::list(
+                    vec![nft].try_into().unwrap(),
+                    100,
+                    start,
+                    end,
+                    None,
+                    AuctionKind::English,
+                    DOT,
+                ),
+            )
+        };
+
+        assert_noop!(list_between(4, 20), Error::<Runtime>::StartInPast);
+        assert_noop!(list_between(10, 10), Error::<Runtime>::EndBeforeStart);
+        assert_noop!(list_between(10, 5), Error::<Runtime>::EndBeforeStart);
+        assert_noop!(
+            list_between(10, 10 + MinAuctionDuration::get() - 1),
+            Error::<Runtime>::AuctionTooShort
+        );
+        assert_noop!(
+            list_between(10, 10 + MaxAuctionDuration::get() + 1),
+            Error::<Runtime>::AuctionTooLong
+        );
+        assert_ok!(list_between(5, 5 + MinAuctionDuration::get()));
+    });
+}
+
+#[test]
+fn sealed_auction_schedule_is_checked() {
+    new_test_ext().execute_with(|| {
+        let nft = mint_nft(None);
+
+        for (reveal_start, buy_now_price) in
+            vec![(START, None), (END, None), (REVEAL_START, Some(200))]
+        {
+            assert_noop!(
+                list(
+                    vec![nft],
+                    AuctionKind::Sealed { reveal_start },
+                    buy_now_price
+                ),
+                Error::<Runtime>::InvalidSealedAuction
+            );
+        }
+    });
+}
+
+#[test]
+fn only_the_owner_can_list_a_nft() {
+    new_test_ext().execute_with(|| {
+        let nft = mint_nft(None);
+        assert_ok!(NFT::transfer(Origin::signed(ALICE), BOB, nft));
+
+        assert_noop!(
+            list(vec![nft], AuctionKind::English, None),
+            Error::<Runtime>::NotOwnerOfNft
+        );
+    });
+}
+
+#[test]
+fn bundle_is_sold_as_a_single_lot() {
+    new_test_ext().execute_with(|| {
+        let with_royalty = mint_nft(Some((DAVE, Permill::from_percent(50))));
+        let without_royalty = mint_nft(None);
+        assert_ok!(list(
+            vec![with_royalty, without_royalty],
+            AuctionKind::English,
+            None
+        ));
+        assert!(has_event(AuctionEvent::ListedNFT(
+            AUCTION,
+            vec![with_royalty, without_royalty],
+            100
+        )));
+        assert_ok!(bid(BOB, 200));
+
+        run_to_block(END + 1);
+
+        assert_eq!(OrmlNFT::tokens(0, 0).unwrap().owner, BOB);
+        assert_eq!(OrmlNFT::tokens(1, 0).unwrap().owner, BOB);
+        // each nft accounts for half of the 180 left after the fee, half of it as royalty
+        assert_eq!(Tokens::free_balance(DOT, &TREASURY), 20);
+        assert_eq!(Tokens::free_balance(DOT, &DAVE), INITIAL_BALANCE + 45);
+        assert_eq!(Tokens::free_balance(DOT, &ALICE), INITIAL_BALANCE + 135);
+        assert!(has_event(AuctionEvent::RoyaltyPaid(AUCTION, DAVE, 45)));
+        assert!(has_event(AuctionEvent::Settled(AUCTION, ALICE, 135, 45)));
+    });
+}
+
+#[test]
+fn unsold_bundle_goes_back_to_the_seller() {
+    new_test_ext().execute_with(|| {
+        let first = mint_nft(None);
+        let second = mint_nft(None);
+        assert_ok!(list(vec![first, second], AuctionKind::English, None));
+
+        run_to_block(END + 1);
+
+        assert_eq!(OrmlNFT::tokens(0, 0).unwrap().owner, ALICE);
+        assert_eq!(OrmlNFT::tokens(1, 0).unwrap().owner, ALICE);
+    });
+}
+
+#[test]
+fn bundle_size_is_checked() {
+    new_test_ext().execute_with(|| {
+        assert_noop!(
+            list(Vec::new(), AuctionKind::English, None),
+            Error::<Runtime>::InvalidBundleSize
+        );
+
+        // larger bundles don't even decode
+        let nfts: Vec<(ClassId, TokenId)> = vec![(0, 0); MaxBundleSize::get() as usize + 1];
+        assert!(
+            BoundedVec::<(ClassId, TokenId), MaxBundleSize>::decode(&mut &nfts.encode()[..])
+                .is_err()
+        );
+    });
+}
+
+#[test]
+fn seller_cannot_commit_to_own_auction() {
+    new_test_ext().execute_with(|| {
+        list_sealed();
+
+        assert_noop!(
+            commit(ALICE, 500, 500),
+            Error::<Runtime>::CannotBuyOwnAuction
+        );
+    });
+}
+
+#[test]
+fn sealed_bid_deposit_covers_the_start_price() {
+    new_test_ext().execute_with(|| {
+        list_sealed();
+
+        assert_noop!(commit(BOB, 0, 0), Error::<Runtime>::DepositTooLow);
+        assert_noop!(commit(BOB, 50, 99), Error::<Runtime>::DepositTooLow);
+        assert_ok!(commit(BOB, 50, 100));
+        assert_eq!(Tokens::reserved_balance(DOT, &BOB), 100);
+    });
+}
+
+#[test]
+fn copied_commitment_cannot_be_revealed() {
+    new_test_ext().execute_with(|| {
+        list_sealed();
+        assert_ok!(commit(BOB, 300, 300));
+        // CHARLIE copies the commitment of BOB
+        assert_ok!(dispatch(
+            CHARLIE,
+            AuctionCall::commit_bid(AUCTION, commitment(BOB, 300, [BOB as u8; 32]), 500)
+        ));
+
+        run_to_block(REVEAL_START);
+        assert_ok!(reveal(BOB, 300));
+        assert_noop!(
+            dispatch(
+                CHARLIE,
+                AuctionCall::reveal_bid(AUCTION, 300, [BOB as u8; 32])
+            ),
+            Error::<Runtime>::InvalidReveal
+        );
+    });
+}
+
+#[test]
+fn close_sealed_charges_the_second_price() {
+    new_test_ext().execute_with(|| {
+        list_sealed();
+        assert_ok!(commit(BOB, 300, 400));
+        assert_ok!(commit(CHARLIE, 200, 200));
+        // never revealed
+        assert_ok!(commit(DAVE, 250, 250));
+        // below the start price
+        assert_ok!(commit(EVE, 50, 100));
+        assert_eq!(AuctionManager::sealed_bid_count(AUCTION), 4);
+
+        run_to_block(REVEAL_START);
+        assert_ok!(reveal(BOB, 300));
+        assert_ok!(reveal(CHARLIE, 200));
+        assert_ok!(reveal(EVE, 50));
+
+        run_to_block(END + 1);
+
+        // BOB wins and pays the second highest bid
+        assert_eq!(OrmlNFT::tokens(0, 0).unwrap().owner, BOB);
+        assert_eq!(Tokens::free_balance(DOT, &BOB), INITIAL_BALANCE - 200);
+        assert_eq!(Tokens::reserved_balance(DOT, &BOB), 0);
+        // losing and rejected bids are refunded
+        assert_eq!(Tokens::free_balance(DOT, &CHARLIE), INITIAL_BALANCE);
+        assert_eq!(Tokens::reserved_balance(DOT, &CHARLIE), 0);
+        assert_eq!(Tokens::free_balance(DOT, &EVE), INITIAL_BALANCE);
+        assert_eq!(Tokens::reserved_balance(DOT, &EVE), 0);
+        // the unrevealed deposit is slashed to the treasury
+        assert_eq!(Tokens::free_balance(DOT, &DAVE), INITIAL_BALANCE - 250);
+        assert_eq!(Tokens::reserved_balance(DOT, &DAVE), 0);
+        // the seller gets the price minus the 10% marketplace fee
+        assert_eq!(Tokens::free_balance(DOT, &ALICE), INITIAL_BALANCE + 180);
+        assert_eq!(Tokens::free_balance(DOT, &TREASURY), 250 + 20);
+
+        assert_eq!(AuctionManager::sealed_bid_count(AUCTION), 0);
+        assert_eq!(SealedBids::<Runtime>::iter_prefix(AUCTION).count(), 0);
+        assert!(AuctionManager::auctions(AUCTION).is_none());
+    });
+}
+
+#[test]
+fn close_sealed_without_second_bid_charges_the_start_price() {
+    new_test_ext().execute_with(|| {
+        list_sealed();
+        assert_ok!(commit(BOB, 300, 300));
+
+        run_to_block(REVEAL_START);
+        assert_ok!(reveal(BOB, 300));
+        run_to_block(END + 1);
+
+        assert_eq!(OrmlNFT::tokens(0, 0).unwrap().owner, BOB);
+        assert_eq!(Tokens::free_balance(DOT, &BOB), INITIAL_BALANCE - 100);
+        assert_eq!(Tokens::reserved_balance(DOT, &BOB), 0);
+    });
+}
+
+#[test]
+fn close_sealed_without_revealed_bid_returns_the_nft() {
+    new_test_ext().execute_with(|| {
+        list_sealed();
+        assert_ok!(commit(BOB, 300, 300));
+
+        run_to_block(END + 1);
+
+        assert_eq!(OrmlNFT::tokens(0, 0).unwrap().owner, ALICE);
+        assert_eq!(Tokens::free_balance(DOT, &BOB), INITIAL_BALANCE - 300);
+        assert_eq!(Tokens::reserved_balance(DOT, &BOB), 0);
+        assert_eq!(Tokens::free_balance(DOT, &TREASURY), 300);
+    });
+}
+
+#[test]
+fn royalties_are_taken_after_the_fee() {
+    new_test_ext().execute_with(|| {
+        list_sealed_with_royalty(Some((DAVE, Permill::from_percent(50))));
+        assert_ok!(commit(BOB, 300, 300));
+        assert_ok!(commit(CHARLIE, 200, 200));
+
+        run_to_block(REVEAL_START);
+        assert_ok!(reveal(BOB, 300));
+        assert_ok!(reveal(CHARLIE, 200));
+        run_to_block(END + 1);
+
+        // 10% fee out of 200, then half of the remaining 180 as royalty
+        assert_eq!(Tokens::free_balance(DOT, &BOB), INITIAL_BALANCE - 200);
+        assert_eq!(Tokens::free_balance(DOT, &TREASURY), 20);
+        assert_eq!(Tokens::free_balance(DOT, &DAVE), INITIAL_BALANCE + 90);
+        assert_eq!(Tokens::free_balance(DOT, &ALICE), INITIAL_BALANCE + 90);
+    });
+}
+
+#[test]
+fn set_fee_is_bounded() {
+    new_test_ext().execute_with(|| {
+        assert_ok!(
+            AuctionCall::set_fee(Permill::from_percent(20)).dispatch_bypass_filter(Origin::root())
+        );
+        assert_eq!(AuctionManager::marketplace_fee(), Permill::from_percent(20));
+
+        assert_noop!(
+            AuctionCall::set_fee(Permill::from_percent(21)).dispatch_bypass_filter(Origin::root()),
+            Error::<Runtime>::FeeTooHigh
+        );
+    });
+}
+
+/// End a sealed auction won by `BOB` at 200 DOT whose settlement fails because the winning
+/// bid isn't reserved anymore.
+fn fail_settlement() {
+    list_sealed();
+    assert_ok!(commit(BOB, 300, 400));
+    assert_ok!(commit(CHARLIE, 200, 200));
+
+    run_to_block(REVEAL_START);
+    assert_ok!(reveal(BOB, 300));
+    assert_ok!(reveal(CHARLIE, 200));
+    Tokens::unreserve(DOT, &BOB, 400);
+    run_to_block(END + 1);
+
+    assert_eq!(
+        AuctionManager::failed_settlements(AUCTION).map(|(_, winner, _)| winner),
+        Some(Some((BOB, 200)))
+    );
+    assert_eq!(
+        OrmlNFT::tokens(0, 0).unwrap().owner,
+        AuctionPalletId::get().into_account()
+    );
+}
+
+#[test]
+fn failed_settlement_can_be_retried() {
+    new_test_ext().execute_with(|| {
+        fail_settlement();
+
+        assert_noop!(
+            dispatch(CHARLIE, AuctionCall::retry_settlement(AUCTION)),
+            Error::<Runtime>::WinningBidNotReserved
+        );
+
+        assert_ok!(Tokens::reserve(DOT, &BOB, 200));
+        assert_ok!(dispatch(CHARLIE, AuctionCall::retry_settlement(AUCTION)));
+
+        assert!(FailedSettlements::<Runtime>::get(AUCTION).is_none());
+        assert_eq!(OrmlNFT::tokens(0, 0).unwrap().owner, BOB);
+        assert_eq!(Tokens::free_balance(DOT, &BOB), INITIAL_BALANCE - 200);
+        assert_eq!(Tokens::reserved_balance(DOT, &BOB), 0);
+        assert_eq!(Tokens::free_balance(DOT, &ALICE), INITIAL_BALANCE + 180);
+    });
+}
+
+#[test]
+fn failed_settlement_can_be_abandoned_by_the_seller() {
+    new_test_ext().execute_with(|| {
+        fail_settlement();
+        assert_ok!(Tokens::reserve(DOT, &BOB, 200));
+
+        assert_noop!(
+            dispatch(CHARLIE, AuctionCall::abandon_settlement(AUCTION)),
+            Error::<Runtime>::NotSettlementParty
+        );
+        assert_noop!(
+            dispatch(ALICE, AuctionCall::abandon_settlement(AUCTION)),
+            Error::<Runtime>::GracePeriodNotOver
+        );
+
+        run_to_block(END + SettlementGracePeriod::get());
+        assert_ok!(dispatch(ALICE, AuctionCall::abandon_settlement(AUCTION)));
+
+        assert!(FailedSettlements::<Runtime>::get(AUCTION).is_none());
+        assert_eq!(OrmlNFT::tokens(0, 0).unwrap().owner, ALICE);
+        assert_eq!(Tokens::free_balance(DOT, &BOB), INITIAL_BALANCE);
+        assert_eq!(Tokens::reserved_balance(DOT, &BOB), 0);
+        assert_noop!(
+            dispatch(ALICE, AuctionCall::retry_settlement(AUCTION)),
+            Error::<Runtime>::SettlementNotFound
+        );
+    });
+}
+
+#[test]
+fn failed_settlement_can_be_abandoned_by_the_winner() {
+    new_test_ext().execute_with(|| {
+        fail_settlement();
+        assert_ok!(Tokens::reserve(DOT, &BOB, 200));
+
+        assert_ok!(dispatch(BOB, AuctionCall::abandon_settlement(AUCTION)));
+
+        assert!(FailedSettlements::<Runtime>::get(AUCTION).is_none());
+        assert_eq!(OrmlNFT::tokens(0, 0).unwrap().owner, ALICE);
+        assert_eq!(Tokens::free_balance(DOT, &BOB), INITIAL_BALANCE);
+        assert_eq!(Tokens::free_balance(DOT, &ALICE), INITIAL_BALANCE);
+    });
+}
+
+#[test]
+fn failed_settlement_can_be_abandoned_by_root() {
+    new_test_ext().execute_with(|| {
+        fail_settlement();
+
+        assert_ok!(AuctionCall::abandon_settlement(AUCTION).dispatch_bypass_filter(Origin::root()));
+
+        assert_eq!(OrmlNFT::tokens(0, 0).unwrap().owner, ALICE);
+        assert_eq!(Tokens::free_balance(DOT, &BOB), INITIAL_BALANCE);
+    });
+}
+
+/// List a nft of `ALICE` with a royalty for `DAVE` in an english auction priced in `KSM`,
+/// and let `BOB` win it at the start price.
+fn sell_in_ksm() {
+    let nft = mint_nft(Some((DAVE, Permill::from_percent(10))));
+    assert_ok!(dispatch(
+        ALICE,
+        AuctionCall::list(
+            vec![nft].try_into().unwrap(),
+            100,
+            START,
+            END,
+            None,
+            AuctionKind::English,
+            KSM
+        )
+    ));
+    assert_ok!(Tokens::deposit(KSM, &BOB, INITIAL_BALANCE));
+    assert_ok!(bid(BOB, 100));
+    run_to_block(END + 1);
+
+    assert_eq!(OrmlNFT::tokens(0, 0).unwrap().owner, BOB);
+    assert_eq!(Tokens::free_balance(KSM, &BOB), INITIAL_BALANCE - 100);
+}
+
+#[test]
+fn payments_below_the_existential_deposit_go_to_the_seller() {
+    new_test_ext().execute_with(|| {
+        sell_in_ksm();
+
+        // the 10 KSM fee and royalty are below the 20 KSM existential deposit
+        assert_eq!(Tokens::total_balance(KSM, &TREASURY), 0);
+        assert_eq!(Tokens::total_balance(KSM, &DAVE), 0);
+        assert_eq!(Tokens::free_balance(KSM, &ALICE), 100);
+        assert!(FailedSettlements::<Runtime>::get(AUCTION).is_none());
+    });
+}
+
+#[test]
+fn payments_below_the_existential_deposit_go_to_existing_accounts() {
+    new_test_ext().execute_with(|| {
+        assert_ok!(Tokens::deposit(KSM, &TREASURY, 20));
+        assert_ok!(Tokens::deposit(KSM, &DAVE, 20));
+        sell_in_ksm();
+
+        assert_eq!(Tokens::free_balance(KSM, &TREASURY), 20 + 10);
+        assert_eq!(Tokens::free_balance(KSM, &DAVE), 20 + 9);
+        assert_eq!(Tokens::free_balance(KSM, &ALICE), 81);
+    });
+}
@@ -0,0 +1,629 @@
+#![cfg_attr(not(feature = "std"), no_std)]
+
+use codec::{Decode, Encode};
+use frame_support::{
+    pallet_prelude::*,
+    traits::{Currency, ExistenceRequirement, ReservableCurrency},
+    transactional, PalletId,
+};
+use frame_system::pallet_prelude::*;
+use orml_traits::NFT;
+use sp_runtime::{
+    traits::{AccountIdConversion, CheckedMul, Saturating, Zero},
+    DispatchResult,
+};
+
+pub use pallet::*;
+
+#[cfg(test)]
+mod mock;
+#[cfg(test)]
+mod tests;
+
+type BalanceOf<T> =
+    <<T as Config>::Currency as Currency<<T as frame_system::Config>::AccountId>>::Balance;
+
+/// An offer to buy any NFT of a class, possibly several of them.
+#[derive(Encode, Decode, Clone, RuntimeDebug, PartialEq, Eq)]
+pub struct ClassOffer<Balance, BlockNumber> {
+    /// Paid for every nft filling the offer
+    pub price: Balance,
+    /// Number of nfts still wanted, `price` is reserved for each of them
+    pub units: u32,
+    /// Block from which the offer can't be filled anymore
+    pub expires: BlockNumber,
+}
+
+#[frame_support::pallet]
+pub mod pallet {
+    use super::*;
+
+    #[pallet::config]
+    pub trait Config: frame_system::Config + kodadot_nft::Config {
+        type Event: From<Event<Self>> + IsType<<Self as frame_system::Config>::Event>;
+
+        /// The pallet in charge of currency transfers
+        type Currency: ReservableCurrency<Self::AccountId>;
+
+        /// The pallet's module id - used to keep NFTs which are on sale
+        #[pallet::constant]
+        type PalletId: Get<PalletId>;
+    }
+
+    #[pallet::error]
+    pub enum Error<T> {
+        /// This can only be done by the owner of the nft
+        NotOwnerOfNft,
+        /// Nft transfer has failed for an unknown reason
+        FailedNftTransfer,
+        /// The nft is not listed for sale
+        ListingNotFound,
+        /// This can only be done by the seller of the nft
+        NotSeller,
+        /// Sellers can't buy their own nft
+        CannotBuyOwnListing,
+        /// The listing price is above what the buyer is willing to pay
+        PriceAboveLimit,
+        /// The nft doesn't exist
+        TokenNotFound,
+        /// Owners can't make offers on their own nft
+        CannotOfferOnOwnNft,
+        /// Offers need a non zero amount
+        InvalidOfferAmount,
+        /// Offers need to expire after the current block
+        OfferExpiryInPast,
+        /// No offer from this bidder on this nft
+        OfferNotFound,
+        /// The offer expired
+        OfferExpired,
+        /// The offer didn't expire yet
+        OfferNotExpired,
+        /// The offer is below what the owner is willing to accept
+        OfferBelowLimit,
+        /// The class doesn't exist
+        ClassNotFound,
+        /// Class offers need at least one unit
+        InvalidOfferUnits,
+        /// The reserved amount of the offer overflows
+        OfferOverflow,
+    }
+
+    /// Store fixed price listings (seller, price) by class id and token id.
+    #[pallet::storage]
+    #[pallet::getter(fn listings)]
+    pub type Listings<T: Config> = StorageDoubleMap<
+        _,
+        Twox64Concat,
+        T::ClassId,
+        Twox64Concat,
+        T::TokenId,
+        (T::AccountId, BalanceOf<T>),
+        OptionQuery,
+    >;
+
+    /// Store standing offers (amount, expiry block) by nft and bidder, the amount is reserved
+    /// from the bidder until the offer is accepted, withdrawn or cleared after expiring.
+    #[pallet::storage]
+    #[pallet::getter(fn offers)]
+    pub type Offers<T: Config> = StorageDoubleMap<
+        _,
+        Twox64Concat,
+        (T::ClassId, T::TokenId),
+        Twox64Concat,
+        T::AccountId,
+        (BalanceOf<T>, T::BlockNumber),
+        OptionQuery,
+    >;
+
+    /// Store offers on any nft of a class by class id and bidder.
+    #[pallet::storage]
+    #[pallet::getter(fn class_offers)]
+    pub type ClassOffers<T: Config> = StorageDoubleMap<
+        _,
+        Twox64Concat,
+        T::ClassId,
+        Twox64Concat,
+        T::AccountId,
+        ClassOffer<BalanceOf<T>, T::BlockNumber>,
+        OptionQuery,
+    >;
+
+    #[pallet::event]
+    #[pallet::generate_deposit(pub(crate) fn deposit_event)]
+    pub enum Event<T: Config> {
+        /// Listed a NFT at a fixed price. \[seller, class id, token id, price\]
+        ListedNFT(T::AccountId, T::ClassId, T::TokenId, BalanceOf<T>),
+        /// Updated the price of a listing. \[class id, token id, price\]
+        PriceUpdated(T::ClassId, T::TokenId, BalanceOf<T>),
+        /// Removed a listing and returned the NFT. \[class id, token id\]
+        ListingCancelled(T::ClassId, T::TokenId),
+        /// Sold a NFT. \[seller, buyer, class id, token id, price\]
+        Sold(
+            T::AccountId,
+            T::AccountId,
+            T::ClassId,
+            T::TokenId,
+            BalanceOf<T>,
+        ),
+        /// Paid the class royalty of a sale. \[class id, token id, recipient, amount\]
+        RoyaltyPaid(T::ClassId, T::TokenId, T::AccountId, BalanceOf<T>),
+        /// Made an offer on a NFT. \[bidder, class id, token id, amount, expiry\]
+        OfferMade(
+            T::AccountId,
+            T::ClassId,
+            T::TokenId,
+            BalanceOf<T>,
+            T::BlockNumber,
+        ),
+        /// Withdrew an offer. \[bidder, class id, token id\]
+        OfferWithdrawn(T::AccountId, T::ClassId, T::TokenId),
+        /// Released the funds of an expired offer. \[bidder, class id, token id\]
+        OfferExpired(T::AccountId, T::ClassId, T::TokenId),
+        /// Accepted an offer. \[seller, bidder, class id, token id, amount\]
+        OfferAccepted(
+            T::AccountId,
+            T::AccountId,
+            T::ClassId,
+            T::TokenId,
+            BalanceOf<T>,
+        ),
+        /// Made an offer on any NFT of a class. \[bidder, class id, price, units, expiry\]
+        ClassOfferMade(T::AccountId, T::ClassId, BalanceOf<T>, u32, T::BlockNumber),
+        /// Withdrew a class offer. \[bidder, class id\]
+        ClassOfferWithdrawn(T::AccountId, T::ClassId),
+        /// Released the funds of an expired class offer. \[bidder, class id\]
+        ClassOfferExpired(T::AccountId, T::ClassId),
+        /// Sold a NFT to a class offer. \[seller, bidder, class id, token id, price\]
+        ClassOfferFilled(
+            T::AccountId,
+            T::AccountId,
+            T::ClassId,
+            T::TokenId,
+            BalanceOf<T>,
+        ),
+    }
+
+    #[pallet::pallet]
+    pub struct Pallet<T>(_);
+
+    #[pallet::hooks]
+    impl<T: Config> Hooks<T::BlockNumber> for Pallet<T> {}
+
+    #[pallet::call]
+    impl<T: Config> Pallet<T> {
+        /// List a NFT for sale at a fixed price, the NFT is kept by the pallet until
+        /// it is sold or the listing is cancelled.
+        /// - `class_id` and `token_id` identify the nft
+        /// - `price` is what a buyer has to pay
+        #[pallet::weight(1_000)]
+        #[transactional]
+        fn list_fixed(
+            origin: OriginFor<T>,
+            class_id: T::ClassId,
+            token_id: T::TokenId,
+            price: BalanceOf<T>,
+        ) -> DispatchResult {
+            let who = ensure_signed(origin)?;
+
+            ensure!(
+                orml_nft::Pallet::<T>::is_owner(&who, (class_id, token_id)),
+                Error::<T>::NotOwnerOfNft
+            );
+            ensure!(
+                <kodadot_nft::Pallet<T> as NFT<T::AccountId>>::transfer(
+                    &who,
+                    &T::PalletId::get().into_account(),
+                    (class_id, token_id)
+                )
+                .is_ok(),
+                Error::<T>::FailedNftTransfer
+            );
+
+            Listings::<T>::insert(class_id, token_id, (who.clone(), price));
+
+            Self::deposit_event(Event::ListedNFT(who, class_id, token_id, price));
+            Ok(())
+        }
+
+        /// Change the price of a listing.
+        /// - `class_id` and `token_id` identify the nft
+        /// - `price` is the new price
+        #[pallet::weight(1_000)]
+        fn update_price(
+            origin: OriginFor<T>,
+            class_id: T::ClassId,
+            token_id: T::TokenId,
+            price: BalanceOf<T>,
+        ) -> DispatchResult {
+            let who = ensure_signed(origin)?;
+
+            Listings::<T>::try_mutate(class_id, token_id, |listing| -> DispatchResult {
+                let (seller, current_price) =
+                    listing.as_mut().ok_or(Error::<T>::ListingNotFound)?;
+                ensure!(*seller == who, Error::<T>::NotSeller);
+                *current_price = price;
+                Ok(())
+            })?;
+
+            Self::deposit_event(Event::PriceUpdated(class_id, token_id, price));
+            Ok(())
+        }
+
+        /// Remove a listing and get the NFT back.
+        /// - `class_id` and `token_id` identify the nft
+        #[pallet::weight(1_000)]
+        #[transactional]
+        fn cancel(
+            origin: OriginFor<T>,
+            class_id: T::ClassId,
+            token_id: T::TokenId,
+        ) -> DispatchResult {
+            let who = ensure_signed(origin)?;
+
+            let (seller, _) =
+                Listings::<T>::get(class_id, token_id).ok_or(Error::<T>::ListingNotFound)?;
+            ensure!(seller == who, Error::<T>::NotSeller);
+
+            Listings::<T>::remove(class_id, token_id);
+            Self::release(&who, (class_id, token_id))?;
+
+            Self::deposit_event(Event::ListingCancelled(class_id, token_id));
+            Ok(())
+        }
+
+        /// Buy a listed NFT at its current price.
+        /// - `class_id` and `token_id` identify the nft
+        /// - `max_price` protects the buyer against a price update landing first
+        #[pallet::weight(1_000)]
+        #[transactional]
+        fn buy(
+            origin: OriginFor<T>,
+            class_id: T::ClassId,
+            token_id: T::TokenId,
+            max_price: BalanceOf<T>,
+        ) -> DispatchResult {
+            let who = ensure_signed(origin)?;
+
+            let (seller, price) =
+                Listings::<T>::take(class_id, token_id).ok_or(Error::<T>::ListingNotFound)?;
+            ensure!(seller != who, Error::<T>::CannotBuyOwnListing);
+            ensure!(price <= max_price, Error::<T>::PriceAboveLimit);
+
+            Self::pay(&who, &seller, (class_id, token_id), price)?;
+            Self::release(&who, (class_id, token_id))?;
+
+            Self::deposit_event(Event::Sold(seller, who, class_id, token_id, price));
+            Ok(())
+        }
+
+        /// Make an offer on any NFT, listed or not. The amount is reserved until the offer
+        /// is accepted, withdrawn or cleared once expired. A new offer replaces the previous
+        /// one from the same bidder.
+        /// - `class_id` and `token_id` identify the nft
+        /// - `amount` is what the bidder pays if the offer is accepted
+        /// - `expires` is the block from which the offer can't be accepted anymore
+        #[pallet::weight(1_000)]
+        #[transactional]
+        fn make_offer(
+            origin: OriginFor<T>,
+            class_id: T::ClassId,
+            token_id: T::TokenId,
+            amount: BalanceOf<T>,
+            expires: T::BlockNumber,
+        ) -> DispatchResult {
+            let who = ensure_signed(origin)?;
+
+            ensure!(
+                Self::seller_of((class_id, token_id))? != who,
+                Error::<T>::CannotOfferOnOwnNft
+            );
+            ensure!(!amount.is_zero(), Error::<T>::InvalidOfferAmount);
+            ensure!(
+                expires > frame_system::Pallet::<T>::block_number(),
+                Error::<T>::OfferExpiryInPast
+            );
+
+            if let Some((previous, _)) = Offers::<T>::get((class_id, token_id), &who) {
+                <T as Config>::Currency::unreserve(&who, previous);
+            }
+            <T as Config>::Currency::reserve(&who, amount)?;
+            Offers::<T>::insert((class_id, token_id), &who, (amount, expires));
+
+            Self::deposit_event(Event::OfferMade(who, class_id, token_id, amount, expires));
+            Ok(())
+        }
+
+        /// Withdraw an offer and release its funds.
+        /// - `class_id` and `token_id` identify the nft
+        #[pallet::weight(1_000)]
+        fn withdraw_offer(
+            origin: OriginFor<T>,
+            class_id: T::ClassId,
+            token_id: T::TokenId,
+        ) -> DispatchResult {
+            let who = ensure_signed(origin)?;
+
+            let (amount, _) =
+                Offers::<T>::take((class_id, token_id), &who).ok_or(Error::<T>::OfferNotFound)?;
+            <T as Config>::Currency::unreserve(&who, amount);
+
+            Self::deposit_event(Event::OfferWithdrawn(who, class_id, token_id));
+            Ok(())
+        }
+
+        /// Release the funds of an expired offer, anyone can call this.
+        /// - `class_id` and `token_id` identify the nft
+        /// - `bidder` is the account which made the offer
+        #[pallet::weight(1_000)]
+        fn clear_expired_offer(
+            origin: OriginFor<T>,
+            class_id: T::ClassId,
+            token_id: T::TokenId,
+            bidder: T::AccountId,
+        ) -> DispatchResult {
+            ensure_signed(origin)?;
+
+            let (amount, expires) =
+                Offers::<T>::get((class_id, token_id), &bidder).ok_or(Error::<T>::OfferNotFound)?;
+            ensure!(
+                expires <= frame_system::Pallet::<T>::block_number(),
+                Error::<T>::OfferNotExpired
+            );
+
+            Offers::<T>::remove((class_id, token_id), &bidder);
+            <T as Config>::Currency::unreserve(&bidder, amount);
+
+            Self::deposit_event(Event::OfferExpired(bidder, class_id, token_id));
+            Ok(())
+        }
+
+        /// Accept an offer on a NFT, as its owner or as the seller of its listing. The
+        /// listing is removed.
+        /// - `class_id` and `token_id` identify the nft
+        /// - `bidder` is the account which made the offer
+        /// - `min_amount` protects the owner against the offer being lowered first
+        #[pallet::weight(1_000)]
+        #[transactional]
+        fn accept_offer(
+            origin: OriginFor<T>,
+            class_id: T::ClassId,
+            token_id: T::TokenId,
+            bidder: T::AccountId,
+            min_amount: BalanceOf<T>,
+        ) -> DispatchResult {
+            let who = ensure_signed(origin)?;
+            let nft = (class_id, token_id);
+
+            ensure!(Self::seller_of(nft)? == who, Error::<T>::NotOwnerOfNft);
+            let (amount, expires) =
+                Offers::<T>::take(nft, &bidder).ok_or(Error::<T>::OfferNotFound)?;
+            ensure!(
+                expires > frame_system::Pallet::<T>::block_number(),
+                Error::<T>::OfferExpired
+            );
+            ensure!(amount >= min_amount, Error::<T>::OfferBelowLimit);
+
+            <T as Config>::Currency::unreserve(&bidder, amount);
+            Self::pay(&bidder, &who, nft, amount)?;
+            if Listings::<T>::take(class_id, token_id).is_some() {
+                Self::release(&bidder, nft)?;
+            } else {
+                ensure!(
+                    <kodadot_nft::Pallet<T> as NFT<T::AccountId>>::transfer(&who, &bidder, nft)
+                        .is_ok(),
+                    Error::<T>::FailedNftTransfer
+                );
+            }
+
+            Self::deposit_event(Event::OfferAccepted(
+                who, bidder, class_id, token_id, amount,
+            ));
+            Ok(())
+        }
+
+        /// Offer to buy any NFT of a class. `price` is reserved for every unit until the
+        /// offer is filled, withdrawn or cleared once expired. A new offer replaces the
+        /// previous one from the same bidder.
+        /// - `class_id` is the class to buy from
+        /// - `price` is paid for every nft filling the offer
+        /// - `units` is the number of nfts wanted
+        /// - `expires` is the block from which the offer can't be filled anymore
+        #[pallet::weight(1_000)]
+        #[transactional]
+        fn make_class_offer(
+            origin: OriginFor<T>,
+            class_id: T::ClassId,
+            price: BalanceOf<T>,
+            units: u32,
+            expires: T::BlockNumber,
+        ) -> DispatchResult {
+            let who = ensure_signed(origin)?;
+
+            ensure!(
+                orml_nft::Pallet::<T>::classes(class_id).is_some(),
+                Error::<T>::ClassNotFound
+            );
+            ensure!(!price.is_zero(), Error::<T>::InvalidOfferAmount);
+            ensure!(units >= 1, Error::<T>::InvalidOfferUnits);
+            ensure!(
+                expires > frame_system::Pallet::<T>::block_number(),
+                Error::<T>::OfferExpiryInPast
+            );
+            let total = price
+                .checked_mul(&BalanceOf::<T>::from(units))
+                .ok_or(Error::<T>::OfferOverflow)?;
+
+            if let Some(previous) = ClassOffers::<T>::get(class_id, &who) {
+                <T as Config>::Currency::unreserve(&who, Self::class_offer_reserve(&previous));
+            }
+            <T as Config>::Currency::reserve(&who, total)?;
+            ClassOffers::<T>::insert(
+                class_id,
+                &who,
+                ClassOffer {
+                    price,
+                    units,
+                    expires,
+                },
+            );
+
+            Self::deposit_event(Event::ClassOfferMade(who, class_id, price, units, expires));
+            Ok(())
+        }
+
+        /// Withdraw a class offer and release its remaining funds.
+        /// - `class_id` is the class of the offer
+        #[pallet::weight(1_000)]
+        fn withdraw_class_offer(origin: OriginFor<T>, class_id: T::ClassId) -> DispatchResult {
+            let who = ensure_signed(origin)?;
+
+            let offer = ClassOffers::<T>::take(class_id, &who).ok_or(Error::<T>::OfferNotFound)?;
+            <T as Config>::Currency::unreserve(&who, Self::class_offer_reserve(&offer));
+
+            Self::deposit_event(Event::ClassOfferWithdrawn(who, class_id));
+            Ok(())
+        }
+
+        /// Release the remaining funds of an expired class offer, anyone can call this.
+        /// - `class_id` is the class of the offer
+        /// - `bidder` is the account which made the offer
+        #[pallet::weight(1_000)]
+        fn clear_expired_class_offer(
+            origin: OriginFor<T>,
+            class_id: T::ClassId,
+            bidder: T::AccountId,
+        ) -> DispatchResult {
+            ensure_signed(origin)?;
+
+            let offer =
+                ClassOffers::<T>::get(class_id, &bidder).ok_or(Error::<T>::OfferNotFound)?;
+            ensure!(
+                offer.expires <= frame_system::Pallet::<T>::block_number(),
+                Error::<T>::OfferNotExpired
+            );
+
+            ClassOffers::<T>::remove(class_id, &bidder);
+            <T as Config>::Currency::unreserve(&bidder, Self::class_offer_reserve(&offer));
+
+            Self::deposit_event(Event::ClassOfferExpired(bidder, class_id));
+            Ok(())
+        }
+
+        /// Sell a NFT to a class offer, as its owner or as the seller of its listing. The
+        /// listing is removed.
+        /// - `class_id` and `token_id` identify the nft
+        /// - `bidder` is the account which made the offer
+        /// - `min_price` protects the owner against the offer being lowered first
+        #[pallet::weight(1_000)]
+        #[transactional]
+        fn fill_class_offer(
+            origin: OriginFor<T>,
+            class_id: T::ClassId,
+            token_id: T::TokenId,
+            bidder: T::AccountId,
+            min_price: BalanceOf<T>,
+        ) -> DispatchResult {
+            let who = ensure_signed(origin)?;
+            let nft = (class_id, token_id);
+
+            ensure!(Self::seller_of(nft)? == who, Error::<T>::NotOwnerOfNft);
+            ensure!(who != bidder, Error::<T>::CannotBuyOwnListing);
+            let price = ClassOffers::<T>::try_mutate_exists(
+                class_id,
+                &bidder,
+                |maybe_offer| -> Result<BalanceOf<T>, DispatchError> {
+                    let offer = maybe_offer.as_mut().ok_or(Error::<T>::OfferNotFound)?;
+                    ensure!(
+                        offer.expires > frame_system::Pallet::<T>::block_number(),
+                        Error::<T>::OfferExpired
+                    );
+                    ensure!(offer.price >= min_price, Error::<T>::OfferBelowLimit);
+
+                    let price = offer.price;
+                    offer.units -= 1;
+                    if offer.units == 0 {
+                        *maybe_offer = None;
+                    }
+                    Ok(price)
+                },
+            )?;
+
+            <T as Config>::Currency::unreserve(&bidder, price);
+            Self::pay(&bidder, &who, nft, price)?;
+            if Listings::<T>::take(class_id, token_id).is_some() {
+                Self::release(&bidder, nft)?;
+            } else {
+                ensure!(
+                    <kodadot_nft::Pallet<T> as NFT<T::AccountId>>::transfer(&who, &bidder, nft)
+                        .is_ok(),
+                    Error::<T>::FailedNftTransfer
+                );
+            }
+
+            Self::deposit_event(Event::ClassOfferFilled(
+                who, bidder, class_id, token_id, price,
+            ));
+            Ok(())
+        }
+    }
+}
+
+impl<T: Config> Pallet<T> {
+    /// Account able to sell a NFT: the seller of its listing, or its owner if it isn't listed.
+    fn seller_of(nft: (T::ClassId, T::TokenId)) -> Result<T::AccountId, DispatchError> {
+        if let Some((seller, _)) = Listings::<T>::get(nft.0, nft.1) {
+            return Ok(seller);
+        }
+        orml_nft::Pallet::<T>::tokens(nft.0, nft.1)
+            .map(|token| token.owner)
+            .ok_or_else(|| Error::<T>::TokenNotFound.into())
+    }
+
+    /// Amount reserved for the remaining units of a class offer.
+    fn class_offer_reserve(offer: &ClassOffer<BalanceOf<T>, T::BlockNumber>) -> BalanceOf<T> {
+        offer
+            .price
+            .saturating_mul(BalanceOf::<T>::from(offer.units))
+    }
+
+    /// Pay `price` from `buyer` to `seller`, minus the royalty of the nft class.
+    fn pay(
+        buyer: &T::AccountId,
+        seller: &T::AccountId,
+        nft: (T::ClassId, T::TokenId),
+        price: BalanceOf<T>,
+    ) -> DispatchResult {
+        let mut seller_amount = price;
+        if let Some((recipient, rate)) = kodadot_nft::Pallet::<T>::royalty(nft.0) {
+            let royalty = rate * price;
+            <T as Config>::Currency::transfer(
+                buyer,
+                &recipient,
+                royalty,
+                ExistenceRequirement::AllowDeath,
+            )?;
+            seller_amount = price.saturating_sub(royalty);
+            Self::deposit_event(Event::RoyaltyPaid(nft.0, nft.1, recipient, royalty));
+        }
+        <T as Config>::Currency::transfer(
+            buyer,
+            seller,
+            seller_amount,
+            ExistenceRequirement::AllowDeath,
+        )
+    }
+
+    /// Hand a NFT kept by the pallet over to `to`.
+    fn release(to: &T::AccountId, nft: (T::ClassId, T::TokenId)) -> DispatchResult {
+        ensure!(
+            <kodadot_nft::Pallet<T> as NFT<T::AccountId>>::transfer(
+                &T::PalletId::get().into_account(),
+                to,
+                nft
+            )
+            .is_ok(),
+            Error::<T>::FailedNftTransfer
+        );
+        Ok(())
+    }
+}
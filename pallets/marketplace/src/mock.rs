@@ -0,0 +1,147 @@
+//! Test runtime for the marketplace pallet.
+
+use crate as kodadot_marketplace;
+use frame_support::{construct_runtime, parameter_types, PalletId};
+use sp_core::H256;
+use sp_runtime::{
+    testing::Header,
+    traits::{BlakeTwo256, IdentityLookup},
+    Permill,
+};
+
+pub type AccountId = u64;
+pub type Balance = u128;
+pub type BlockNumber = u64;
+pub type ClassId = u32;
+pub type TokenId = u64;
+
+pub const ALICE: AccountId = 1;
+pub const BOB: AccountId = 2;
+pub const CHARLIE: AccountId = 3;
+pub const DAVE: AccountId = 4;
+
+pub const INITIAL_BALANCE: Balance = 1_000;
+
+parameter_types! {
+    pub const BlockHashCount: u64 = 250;
+}
+
+impl frame_system::Config for Runtime {
+    type BaseCallFilter = ();
+    type BlockWeights = ();
+    type BlockLength = ();
+    type Origin = Origin;
+    type Call = Call;
+    type Index = u64;
+    type BlockNumber = BlockNumber;
+    type Hash = H256;
+    type Hashing = BlakeTwo256;
+    type AccountId = AccountId;
+    type Lookup = IdentityLookup<Self::AccountId>;
+    type Header = Header;
+    type Event = Event;
+    type BlockHashCount = BlockHashCount;
+    type DbWeight = ();
+    type Version = ();
+    type PalletInfo = PalletInfo;
+    type AccountData = pallet_balances::AccountData<Balance>;
+    type OnNewAccount = ();
+    type OnKilledAccount = ();
+    type SystemWeightInfo = ();
+    type SS58Prefix = ();
+    type OnSetCode = ();
+}
+
+parameter_types! {
+    pub const ExistentialDeposit: Balance = 1;
+}
+
+impl pallet_balances::Config for Runtime {
+    type MaxLocks = ();
+    type Balance = Balance;
+    type Event = Event;
+    type DustRemoval = ();
+    type ExistentialDeposit = ExistentialDeposit;
+    type AccountStore = System;
+    type WeightInfo = ();
+}
+
+impl orml_nft::Config for Runtime {
+    type ClassId = ClassId;
+    type TokenId = TokenId;
+    type ClassData = kodadot_nft::ClassData<AccountId>;
+    type TokenData = kodadot_nft::TokenData;
+}
+
+parameter_types! {
+    pub const ClassDeposit: Balance = 1;
+    pub const TokenDeposit: Balance = 1;
+    pub const DepositPerByte: Balance = 1;
+    pub const MaxMetadataLength: u32 = 32;
+    pub const MaxRoyalty: Permill = Permill::from_percent(50);
+    pub const AttributeDeposit: Balance = 1;
+    pub const MaxAttributeKeyLength: u32 = 32;
+    pub const MaxAttributeValueLength: u32 = 32;
+    pub const MaxAttributes: u32 = 4;
+}
+
+impl kodadot_nft::Config for Runtime {
+    type Event = Event;
+    type Currency = Balances;
+    type ClassDeposit = ClassDeposit;
+    type TokenDeposit = TokenDeposit;
+    type DepositPerByte = DepositPerByte;
+    type MaxMetadataLength = MaxMetadataLength;
+    type MaxRoyalty = MaxRoyalty;
+    type AttributeDeposit = AttributeDeposit;
+    type MaxAttributeKeyLength = MaxAttributeKeyLength;
+    type MaxAttributeValueLength = MaxAttributeValueLength;
+    type MaxAttributes = MaxAttributes;
+}
+
+parameter_types! {
+    pub const MarketplacePalletId: PalletId = PalletId(*b"kod/mrkt");
+}
+
+impl kodadot_marketplace::Config for Runtime {
+    type Event = Event;
+    type Currency = Balances;
+    type PalletId = MarketplacePalletId;
+}
+
+type UncheckedExtrinsic = frame_system::mocking::MockUncheckedExtrinsic<Runtime>;
+type Block = frame_system::mocking::MockBlock<Runtime>;
+
+construct_runtime!(
+    pub enum Runtime where
+        Block = Block,
+        NodeBlock = Block,
+        UncheckedExtrinsic = UncheckedExtrinsic,
+    {
+        System: frame_system::{Pallet, Call, Config, Storage, Event<T>},
+        Balances: pallet_balances::{Pallet, Call, Storage, Config<T>, Event<T>},
+        OrmlNFT: orml_nft::{Pallet, Storage, Config<T>},
+        NFT: kodadot_nft::{Pallet, Call, Storage, Event<T>},
+        Marketplace: kodadot_marketplace::{Pallet, Call, Storage, Event<T>},
+    }
+);
+
+pub fn new_test_ext() -> sp_io::TestExternalities {
+    let mut storage = frame_system::GenesisConfig::default()
+        .build_storage::<Runtime>()
+        .unwrap();
+    pallet_balances::GenesisConfig::<Runtime> {
+        balances: vec![
+            (ALICE, INITIAL_BALANCE),
+            (BOB, INITIAL_BALANCE),
+            (CHARLIE, INITIAL_BALANCE),
+            (DAVE, INITIAL_BALANCE),
+        ],
+    }
+    .assimilate_storage(&mut storage)
+    .unwrap();
+
+    let mut ext = sp_io::TestExternalities::new(storage);
+    ext.execute_with(|| System::set_block_number(1));
+    ext
+}
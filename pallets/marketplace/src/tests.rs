@@ -0,0 +1,557 @@
+//! Unit tests for the marketplace pallet.
+
+use crate::{mock::*, Error, Listings};
+use frame_support::{
+    assert_noop, assert_ok,
+    dispatch::DispatchResultWithPostInfo,
+    traits::{Currency, Get, ReservableCurrency, UnfilteredDispatchable},
+};
+use sp_runtime::{traits::AccountIdConversion, Permill};
+
+type MarketplaceCall = crate::Call<Runtime>;
+type MarketplaceEvent = crate::Event<Runtime>;
+
+fn dispatch(who: AccountId, call: MarketplaceCall) -> DispatchResultWithPostInfo {
+    call.dispatch_bypass_filter(Origin::signed(who))
+}
+
+fn has_event(event: MarketplaceEvent) -> bool {
+    let event: Event = event.into();
+    System::events().iter().any(|record| record.event == event)
+}
+
+fn pallet_account() -> AccountId {
+    MarketplacePalletId::get().into_account()
+}
+
+fn owner((class_id, token_id): (ClassId, TokenId)) -> AccountId {
+    OrmlNFT::tokens(class_id, token_id).unwrap().owner
+}
+
+/// Create a class with `royalty` and mint its first nft for `ALICE`.
+fn mint_nft(royalty: Option<(AccountId, Permill)>) -> (ClassId, TokenId) {
+    let class_id = OrmlNFT::next_class_id();
+    assert_ok!(NFT::create_class(
+        Origin::signed(ALICE),
+        Vec::new(),
+        royalty,
+        None
+    ));
+    assert_ok!(NFT::mint(
+        Origin::signed(ALICE),
+        ALICE,
+        class_id,
+        Vec::new(),
+        1
+    ));
+    (class_id, 0)
+}
+
+/// Mint a nft for `ALICE` and list it at `price`.
+fn list_nft(price: Balance) -> (ClassId, TokenId) {
+    let nft = mint_nft(None);
+    assert_ok!(dispatch(
+        ALICE,
+        MarketplaceCall::list_fixed(nft.0, nft.1, price)
+    ));
+    nft
+}
+
+#[test]
+fn listed_nft_is_kept_by_the_pallet() {
+    new_test_ext().execute_with(|| {
+        let nft = mint_nft(None);
+
+        assert_noop!(
+            dispatch(BOB, MarketplaceCall::list_fixed(nft.0, nft.1, 100)),
+            Error::<Runtime>::NotOwnerOfNft
+        );
+        assert_ok!(dispatch(
+            ALICE,
+            MarketplaceCall::list_fixed(nft.0, nft.1, 100)
+        ));
+
+        assert_eq!(owner(nft), pallet_account());
+        assert_eq!(Marketplace::listings(nft.0, nft.1), Some((ALICE, 100)));
+        assert!(has_event(MarketplaceEvent::ListedNFT(
+            ALICE, nft.0, nft.1, 100
+        )));
+    });
+}
+
+#[test]
+fn buy_pays_the_seller() {
+    new_test_ext().execute_with(|| {
+        let nft = list_nft(100);
+        let seller_balance = Balances::free_balance(&ALICE);
+
+        assert_noop!(
+            dispatch(BOB, MarketplaceCall::buy(nft.0, nft.1, 99)),
+            Error::<Runtime>::PriceAboveLimit
+        );
+        assert_ok!(dispatch(BOB, MarketplaceCall::buy(nft.0, nft.1, 100)));
+
+        assert_eq!(owner(nft), BOB);
+        assert_eq!(Balances::free_balance(&BOB), INITIAL_BALANCE - 100);
+        assert_eq!(Balances::free_balance(&ALICE), seller_balance + 100);
+        assert!(Listings::<Runtime>::get(nft.0, nft.1).is_none());
+        assert!(has_event(MarketplaceEvent::Sold(
+            ALICE, BOB, nft.0, nft.1, 100
+        )));
+        assert_noop!(
+            dispatch(CHARLIE, MarketplaceCall::buy(nft.0, nft.1, 100)),
+            Error::<Runtime>::ListingNotFound
+        );
+    });
+}
+
+#[test]
+fn seller_cannot_buy_own_listing() {
+    new_test_ext().execute_with(|| {
+        let nft = list_nft(100);
+
+        assert_noop!(
+            dispatch(ALICE, MarketplaceCall::buy(nft.0, nft.1, 100)),
+            Error::<Runtime>::CannotBuyOwnListing
+        );
+    });
+}
+
+#[test]
+fn only_the_seller_can_update_the_price() {
+    new_test_ext().execute_with(|| {
+        let nft = list_nft(100);
+
+        assert_noop!(
+            dispatch(BOB, MarketplaceCall::update_price(nft.0, nft.1, 50)),
+            Error::<Runtime>::NotSeller
+        );
+        assert_ok!(dispatch(
+            ALICE,
+            MarketplaceCall::update_price(nft.0, nft.1, 150)
+        ));
+
+        assert_eq!(Marketplace::listings(nft.0, nft.1), Some((ALICE, 150)));
+        assert!(has_event(MarketplaceEvent::PriceUpdated(nft.0, nft.1, 150)));
+        // the buyer is protected against the price update landing first
+        assert_noop!(
+            dispatch(BOB, MarketplaceCall::buy(nft.0, nft.1, 100)),
+            Error::<Runtime>::PriceAboveLimit
+        );
+    });
+}
+
+#[test]
+fn only_the_seller_can_cancel() {
+    new_test_ext().execute_with(|| {
+        let nft = list_nft(100);
+
+        assert_noop!(
+            dispatch(BOB, MarketplaceCall::cancel(nft.0, nft.1)),
+            Error::<Runtime>::NotSeller
+        );
+        assert_ok!(dispatch(ALICE, MarketplaceCall::cancel(nft.0, nft.1)));
+
+        assert_eq!(owner(nft), ALICE);
+        assert!(Listings::<Runtime>::get(nft.0, nft.1).is_none());
+        assert!(has_event(MarketplaceEvent::ListingCancelled(nft.0, nft.1)));
+        assert_noop!(
+            dispatch(ALICE, MarketplaceCall::cancel(nft.0, nft.1)),
+            Error::<Runtime>::ListingNotFound
+        );
+        assert_noop!(
+            dispatch(ALICE, MarketplaceCall::update_price(nft.0, nft.1, 150)),
+            Error::<Runtime>::ListingNotFound
+        );
+    });
+}
+
+#[test]
+fn sale_pays_the_class_royalty() {
+    new_test_ext().execute_with(|| {
+        let nft = mint_nft(Some((DAVE, Permill::from_percent(10))));
+        assert_ok!(dispatch(
+            ALICE,
+            MarketplaceCall::list_fixed(nft.0, nft.1, 100)
+        ));
+        let seller_balance = Balances::free_balance(&ALICE);
+
+        assert_ok!(dispatch(BOB, MarketplaceCall::buy(nft.0, nft.1, 100)));
+
+        assert_eq!(Balances::free_balance(&BOB), INITIAL_BALANCE - 100);
+        assert_eq!(Balances::free_balance(&DAVE), INITIAL_BALANCE + 10);
+        assert_eq!(Balances::free_balance(&ALICE), seller_balance + 90);
+        assert!(has_event(MarketplaceEvent::RoyaltyPaid(
+            nft.0, nft.1, DAVE, 10
+        )));
+    });
+}
+
+#[test]
+fn offer_reserves_its_amount() {
+    new_test_ext().execute_with(|| {
+        let nft = mint_nft(None);
+
+        assert_noop!(
+            dispatch(ALICE, MarketplaceCall::make_offer(nft.0, nft.1, 100, 10)),
+            Error::<Runtime>::CannotOfferOnOwnNft
+        );
+        assert_noop!(
+            dispatch(BOB, MarketplaceCall::make_offer(nft.0, nft.1, 0, 10)),
+            Error::<Runtime>::InvalidOfferAmount
+        );
+        assert_noop!(
+            dispatch(BOB, MarketplaceCall::make_offer(nft.0, nft.1, 100, 1)),
+            Error::<Runtime>::OfferExpiryInPast
+        );
+        assert_ok!(dispatch(
+            BOB,
+            MarketplaceCall::make_offer(nft.0, nft.1, 100, 10)
+        ));
+        assert_eq!(Balances::reserved_balance(&BOB), 100);
+        assert!(has_event(MarketplaceEvent::OfferMade(
+            BOB, nft.0, nft.1, 100, 10
+        )));
+
+        // a new offer replaces the previous one
+        assert_ok!(dispatch(
+            BOB,
+            MarketplaceCall::make_offer(nft.0, nft.1, 80, 10)
+        ));
+        assert_eq!(Balances::reserved_balance(&BOB), 80);
+        assert_eq!(Marketplace::offers(nft, BOB), Some((80, 10)));
+    });
+}
+
+#[test]
+fn offer_can_be_withdrawn() {
+    new_test_ext().execute_with(|| {
+        let nft = mint_nft(None);
+        assert_ok!(dispatch(
+            BOB,
+            MarketplaceCall::make_offer(nft.0, nft.1, 100, 10)
+        ));
+
+        assert_ok!(dispatch(BOB, MarketplaceCall::withdraw_offer(nft.0, nft.1)));
+
+        assert_eq!(Balances::reserved_balance(&BOB), 0);
+        assert_eq!(Balances::free_balance(&BOB), INITIAL_BALANCE);
+        assert!(Marketplace::offers(nft, BOB).is_none());
+        assert_noop!(
+            dispatch(BOB, MarketplaceCall::withdraw_offer(nft.0, nft.1)),
+            Error::<Runtime>::OfferNotFound
+        );
+    });
+}
+
+#[test]
+fn owner_accepts_offer_on_unlisted_nft() {
+    new_test_ext().execute_with(|| {
+        let nft = mint_nft(None);
+        assert_ok!(dispatch(
+            BOB,
+            MarketplaceCall::make_offer(nft.0, nft.1, 100, 10)
+        ));
+        let seller_balance = Balances::free_balance(&ALICE);
+
+        assert_noop!(
+            dispatch(
+                CHARLIE,
+                MarketplaceCall::accept_offer(nft.0, nft.1, BOB, 100)
+            ),
+            Error::<Runtime>::NotOwnerOfNft
+        );
+        assert_noop!(
+            dispatch(ALICE, MarketplaceCall::accept_offer(nft.0, nft.1, BOB, 101)),
+            Error::<Runtime>::OfferBelowLimit
+        );
+        assert_ok!(dispatch(
+            ALICE,
+            MarketplaceCall::accept_offer(nft.0, nft.1, BOB, 100)
+        ));
+
+        assert_eq!(owner(nft), BOB);
+        assert_eq!(Balances::reserved_balance(&BOB), 0);
+        assert_eq!(Balances::free_balance(&BOB), INITIAL_BALANCE - 100);
+        assert_eq!(Balances::free_balance(&ALICE), seller_balance + 100);
+        assert!(Marketplace::offers(nft, BOB).is_none());
+        assert!(has_event(MarketplaceEvent::OfferAccepted(
+            ALICE, BOB, nft.0, nft.1, 100
+        )));
+    });
+}
+
+#[test]
+fn seller_accepts_offer_on_listed_nft() {
+    new_test_ext().execute_with(|| {
+        let nft = list_nft(500);
+        assert_ok!(dispatch(
+            BOB,
+            MarketplaceCall::make_offer(nft.0, nft.1, 100, 10)
+        ));
+
+        assert_ok!(dispatch(
+            ALICE,
+            MarketplaceCall::accept_offer(nft.0, nft.1, BOB, 100)
+        ));
+
+        assert_eq!(owner(nft), BOB);
+        assert!(Listings::<Runtime>::get(nft.0, nft.1).is_none());
+        assert_eq!(Balances::free_balance(&BOB), INITIAL_BALANCE - 100);
+    });
+}
+
+#[test]
+fn expired_offer_cannot_be_accepted() {
+    new_test_ext().execute_with(|| {
+        let nft = mint_nft(None);
+        assert_ok!(dispatch(
+            BOB,
+            MarketplaceCall::make_offer(nft.0, nft.1, 100, 10)
+        ));
+
+        System::set_block_number(10);
+        assert_noop!(
+            dispatch(ALICE, MarketplaceCall::accept_offer(nft.0, nft.1, BOB, 100)),
+            Error::<Runtime>::OfferExpired
+        );
+    });
+}
+
+#[test]
+fn expired_offer_can_be_cleared_by_anyone() {
+    new_test_ext().execute_with(|| {
+        let nft = mint_nft(None);
+        assert_ok!(dispatch(
+            BOB,
+            MarketplaceCall::make_offer(nft.0, nft.1, 100, 10)
+        ));
+
+        System::set_block_number(9);
+        assert_noop!(
+            dispatch(
+                CHARLIE,
+                MarketplaceCall::clear_expired_offer(nft.0, nft.1, BOB)
+            ),
+            Error::<Runtime>::OfferNotExpired
+        );
+
+        System::set_block_number(10);
+        assert_ok!(dispatch(
+            CHARLIE,
+            MarketplaceCall::clear_expired_offer(nft.0, nft.1, BOB)
+        ));
+        assert_eq!(Balances::reserved_balance(&BOB), 0);
+        assert_eq!(Balances::free_balance(&BOB), INITIAL_BALANCE);
+        assert!(Marketplace::offers(nft, BOB).is_none());
+        assert!(has_event(MarketplaceEvent::OfferExpired(BOB, nft.0, nft.1)));
+    });
+}
+
+/// Mint a class of two nfts for `ALICE` and let `BOB` offer 50 for up to three of them.
+fn offer_on_class() -> ClassId {
+    let (class_id, _) = mint_nft(None);
+    assert_ok!(NFT::mint(
+        Origin::signed(ALICE),
+        ALICE,
+        class_id,
+        Vec::new(),
+        1
+    ));
+    assert_ok!(dispatch(
+        BOB,
+        MarketplaceCall::make_class_offer(class_id, 50, 3, 10)
+    ));
+    class_id
+}
+
+#[test]
+fn class_offer_reserves_every_unit() {
+    new_test_ext().execute_with(|| {
+        let class_id = offer_on_class();
+
+        assert_eq!(Balances::reserved_balance(&BOB), 150);
+        assert!(has_event(MarketplaceEvent::ClassOfferMade(
+            BOB, class_id, 50, 3, 10
+        )));
+
+        // a new offer replaces the previous one
+        assert_ok!(dispatch(
+            BOB,
+            MarketplaceCall::make_class_offer(class_id, 40, 2, 10)
+        ));
+        assert_eq!(Balances::reserved_balance(&BOB), 80);
+    });
+}
+
+#[test]
+fn class_offer_parameters_are_checked() {
+    new_test_ext().execute_with(|| {
+        let (class_id, _) = mint_nft(None);
+
+        assert_noop!(
+            dispatch(
+                BOB,
+                MarketplaceCall::make_class_offer(class_id + 1, 50, 1, 10)
+            ),
+            Error::<Runtime>::ClassNotFound
+        );
+        assert_noop!(
+            dispatch(BOB, MarketplaceCall::make_class_offer(class_id, 0, 1, 10)),
+            Error::<Runtime>::InvalidOfferAmount
+        );
+        assert_noop!(
+            dispatch(BOB, MarketplaceCall::make_class_offer(class_id, 50, 0, 10)),
+            Error::<Runtime>::InvalidOfferUnits
+        );
+        assert_noop!(
+            dispatch(BOB, MarketplaceCall::make_class_offer(class_id, 50, 1, 1)),
+            Error::<Runtime>::OfferExpiryInPast
+        );
+        assert_noop!(
+            dispatch(
+                BOB,
+                MarketplaceCall::make_class_offer(class_id, Balance::MAX, 2, 10)
+            ),
+            Error::<Runtime>::OfferOverflow
+        );
+    });
+}
+
+#[test]
+fn class_offer_is_filled_unit_by_unit() {
+    new_test_ext().execute_with(|| {
+        let class_id = offer_on_class();
+        let seller_balance = Balances::free_balance(&ALICE);
+
+        assert_noop!(
+            dispatch(
+                CHARLIE,
+                MarketplaceCall::fill_class_offer(class_id, 0, BOB, 50)
+            ),
+            Error::<Runtime>::NotOwnerOfNft
+        );
+        assert_noop!(
+            dispatch(
+                ALICE,
+                MarketplaceCall::fill_class_offer(class_id, 0, BOB, 51)
+            ),
+            Error::<Runtime>::OfferBelowLimit
+        );
+        assert_ok!(dispatch(
+            ALICE,
+            MarketplaceCall::fill_class_offer(class_id, 0, BOB, 50)
+        ));
+
+        assert_eq!(owner((class_id, 0)), BOB);
+        assert_eq!(Balances::reserved_balance(&BOB), 100);
+        assert_eq!(Balances::free_balance(&BOB), INITIAL_BALANCE - 150);
+        assert_eq!(Balances::free_balance(&ALICE), seller_balance + 50);
+        assert_eq!(Marketplace::class_offers(class_id, BOB).unwrap().units, 2);
+        assert!(has_event(MarketplaceEvent::ClassOfferFilled(
+            ALICE, BOB, class_id, 0, 50
+        )));
+    });
+}
+
+#[test]
+fn class_offer_can_be_filled_by_a_listing() {
+    new_test_ext().execute_with(|| {
+        let class_id = offer_on_class();
+        assert_ok!(dispatch(
+            ALICE,
+            MarketplaceCall::list_fixed(class_id, 1, 500)
+        ));
+
+        assert_ok!(dispatch(
+            ALICE,
+            MarketplaceCall::fill_class_offer(class_id, 1, BOB, 50)
+        ));
+
+        assert_eq!(owner((class_id, 1)), BOB);
+        assert!(Listings::<Runtime>::get(class_id, 1).is_none());
+    });
+}
+
+#[test]
+fn last_unit_closes_the_class_offer() {
+    new_test_ext().execute_with(|| {
+        let class_id = offer_on_class();
+        assert_ok!(dispatch(
+            BOB,
+            MarketplaceCall::make_class_offer(class_id, 50, 1, 10)
+        ));
+
+        assert_ok!(dispatch(
+            ALICE,
+            MarketplaceCall::fill_class_offer(class_id, 0, BOB, 50)
+        ));
+
+        assert!(Marketplace::class_offers(class_id, BOB).is_none());
+        assert_eq!(Balances::reserved_balance(&BOB), 0);
+        assert_noop!(
+            dispatch(
+                ALICE,
+                MarketplaceCall::fill_class_offer(class_id, 1, BOB, 50)
+            ),
+            Error::<Runtime>::OfferNotFound
+        );
+    });
+}
+
+#[test]
+fn withdrawing_a_partly_filled_class_offer_releases_the_rest() {
+    new_test_ext().execute_with(|| {
+        let class_id = offer_on_class();
+        assert_ok!(dispatch(
+            ALICE,
+            MarketplaceCall::fill_class_offer(class_id, 0, BOB, 50)
+        ));
+
+        assert_ok!(dispatch(
+            BOB,
+            MarketplaceCall::withdraw_class_offer(class_id)
+        ));
+
+        assert_eq!(Balances::reserved_balance(&BOB), 0);
+        assert_eq!(Balances::free_balance(&BOB), INITIAL_BALANCE - 50);
+        assert!(Marketplace::class_offers(class_id, BOB).is_none());
+        assert!(has_event(MarketplaceEvent::ClassOfferWithdrawn(
+            BOB, class_id
+        )));
+    });
+}
+
+#[test]
+fn expired_class_offer_can_be_cleared_by_anyone() {
+    new_test_ext().execute_with(|| {
+        let class_id = offer_on_class();
+
+        System::set_block_number(9);
+        assert_noop!(
+            dispatch(
+                CHARLIE,
+                MarketplaceCall::clear_expired_class_offer(class_id, BOB)
+            ),
+            Error::<Runtime>::OfferNotExpired
+        );
+
+        System::set_block_number(10);
+        assert_noop!(
+            dispatch(
+                ALICE,
+                MarketplaceCall::fill_class_offer(class_id, 0, BOB, 50)
+            ),
+            Error::<Runtime>::OfferExpired
+        );
+        assert_ok!(dispatch(
+            CHARLIE,
+            MarketplaceCall::clear_expired_class_offer(class_id, BOB)
+        ));
+        assert_eq!(Balances::reserved_balance(&BOB), 0);
+        assert_eq!(Balances::free_balance(&BOB), INITIAL_BALANCE);
+        assert!(has_event(MarketplaceEvent::ClassOfferExpired(
+            BOB, class_id
+        )));
+    });
+}
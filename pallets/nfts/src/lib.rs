@@ -1,26 +1,54 @@
 #![cfg_attr(not(feature = "std"), no_std)]
 
 use codec::{Decode, Encode};
-use frame_support::{pallet_prelude::*, transactional};
+use frame_support::{
+    pallet_prelude::*,
+    traits::{Currency, ReservableCurrency},
+    transactional,
+};
 use frame_system::pallet_prelude::*;
 use orml_traits::NFT;
 #[cfg(feature = "std")]
 use serde::{Deserialize, Serialize};
-use sp_runtime::{traits::StaticLookup, DispatchResult};
+use sp_runtime::{
+    traits::{Saturating, StaticLookup, Zero},
+    DispatchResult, Permill,
+};
 use sp_std::vec::Vec;
 
 pub use pallet::*;
 
+#[cfg(test)]
+mod mock;
+#[cfg(test)]
+mod tests;
+
 #[derive(Encode, Decode, Clone, RuntimeDebug, PartialEq, Eq, Default)]
 #[cfg_attr(feature = "std", derive(Serialize, Deserialize))]
-pub struct ClassData {}
+pub struct ClassData<AccountId> {
+    /// Account receiving royalties on every sale of the class tokens and its cut
+    pub royalty: Option<(AccountId, Permill)>,
+    /// Whether the class metadata and the metadata of all its tokens are frozen for good
+    pub frozen: bool,
+    /// Whether token owners can edit the attributes of their tokens, besides the class owner
+    pub holder_attributes: bool,
+    /// Maximum number of tokens of the class in existence, set at creation
+    pub max_supply: Option<u32>,
+    /// Whether minting is closed for good
+    pub supply_locked: bool,
+}
 
 #[derive(Encode, Decode, Clone, RuntimeDebug, PartialEq, Eq, Default)]
 #[cfg_attr(feature = "std", derive(Serialize, Deserialize))]
-pub struct TokenData {}
+pub struct TokenData {
+    /// Whether the token metadata is frozen for good
+    pub frozen: bool,
+}
 
 pub type ClassIdOf<T> = <T as orml_nft::Config>::ClassId;
 pub type TokenIdOf<T> = <T as orml_nft::Config>::TokenId;
+type BalanceOf<T> =
+    <<T as Config>::Currency as Currency<<T as frame_system::Config>::AccountId>>::Balance;
 
 #[frame_support::pallet]
 pub mod pallet {
@@ -28,9 +56,49 @@ pub mod pallet {
 
     #[pallet::config]
     pub trait Config:
-        frame_system::Config + orml_nft::Config<ClassData = ClassData, TokenData = TokenData>
+        frame_system::Config
+        + orml_nft::Config<ClassData = ClassData<Self::AccountId>, TokenData = TokenData>
     {
         type Event: From<Event<Self>> + IsType<<Self as frame_system::Config>::Event>;
+
+        /// Currency in which storage deposits are reserved
+        type Currency: ReservableCurrency<Self::AccountId>;
+
+        /// Deposit reserved for storing a class, on top of its metadata bytes
+        #[pallet::constant]
+        type ClassDeposit: Get<BalanceOf<Self>>;
+
+        /// Deposit reserved for storing a token, on top of its metadata bytes
+        #[pallet::constant]
+        type TokenDeposit: Get<BalanceOf<Self>>;
+
+        /// Deposit reserved for every byte of class or token metadata
+        #[pallet::constant]
+        type DepositPerByte: Get<BalanceOf<Self>>;
+
+        /// Maximum length of class and token metadata
+        #[pallet::constant]
+        type MaxMetadataLength: Get<u32>;
+
+        /// Maximum cut of a sale a class royalty can take
+        #[pallet::constant]
+        type MaxRoyalty: Get<Permill>;
+
+        /// Deposit reserved for storing an attribute, on top of its key and value bytes
+        #[pallet::constant]
+        type AttributeDeposit: Get<BalanceOf<Self>>;
+
+        /// Maximum length of an attribute key
+        #[pallet::constant]
+        type MaxAttributeKeyLength: Get<u32>;
+
+        /// Maximum length of an attribute value
+        #[pallet::constant]
+        type MaxAttributeValueLength: Get<u32>;
+
+        /// Maximum number of attributes of a class, or of a token
+        #[pallet::constant]
+        type MaxAttributes: Get<u32>;
     }
 
     #[pallet::error]
@@ -41,6 +109,30 @@ pub mod pallet {
         NoPermission,
         /// Quantity is invalid. need >= 1
         InvalidQuantity,
+        /// Class still has tokens and can not be destroyed
+        ClassNotEmpty,
+        /// No ownership transfer of the class is pending for the caller
+        NoPendingOwnership,
+        /// TokenId not found
+        TokenIdNotFound,
+        /// Metadata is frozen and can not be changed anymore
+        MetadataFrozen,
+        /// Metadata is longer than `MaxMetadataLength`
+        MetadataTooLong,
+        /// Royalty is higher than `MaxRoyalty`
+        RoyaltyTooHigh,
+        /// Attribute key is longer than `MaxAttributeKeyLength`
+        AttributeKeyTooLong,
+        /// Attribute value is longer than `MaxAttributeValueLength`
+        AttributeValueTooLong,
+        /// No attribute is stored under this key
+        AttributeNotFound,
+        /// The class or token already has `MaxAttributes` attributes
+        TooManyAttributes,
+        /// Minting would take the class over its maximum supply
+        MaxSupplyReached,
+        /// The class supply is locked, no more tokens can be minted
+        SupplyLocked,
     }
 
     #[pallet::event]
@@ -48,10 +140,87 @@ pub mod pallet {
     pub enum Event<T: Config> {
         /// Created NFT class. \[owner, class_id\]
         CreatedClass(T::AccountId, ClassIdOf<T>),
-        /// Minted NFT token. \[from, to, class_id, quantity\]
-        MintedToken(T::AccountId, T::AccountId, ClassIdOf<T>, u32),
+        /// Minted NFT tokens. \[from, to, class_id, first_token_id, last_token_id\]
+        MintedToken(
+            T::AccountId,
+            T::AccountId,
+            ClassIdOf<T>,
+            TokenIdOf<T>,
+            TokenIdOf<T>,
+        ),
+        /// Burned NFT token. \[owner, class_id, token_id\]
+        Burned(T::AccountId, ClassIdOf<T>, TokenIdOf<T>),
+        /// Destroyed NFT class. \[owner, class_id\]
+        DestroyedClass(T::AccountId, ClassIdOf<T>),
+        /// Class ownership offered to a new account. \[owner, new_owner, class_id\]
+        ClassOwnershipOffered(T::AccountId, T::AccountId, ClassIdOf<T>),
+        /// Class ownership accepted by its new owner. \[old_owner, new_owner, class_id\]
+        ClassOwnershipTransferred(T::AccountId, T::AccountId, ClassIdOf<T>),
+        /// Transferred NFT token. \[from, to, class_id, token_id\]
+        Transferred(T::AccountId, T::AccountId, ClassIdOf<T>, TokenIdOf<T>),
+        /// Updated NFT class metadata. \[class_id\]
+        ClassMetadataSet(ClassIdOf<T>),
+        /// Updated NFT token metadata. \[class_id, token_id\]
+        TokenMetadataSet(ClassIdOf<T>, TokenIdOf<T>),
+        /// Froze the metadata of a class, or of a single token. \[class_id, token_id\]
+        MetadataFrozen(ClassIdOf<T>, Option<TokenIdOf<T>>),
+        /// Allowed or forbade token owners to edit their token attributes. \[class_id, allowed\]
+        HolderAttributesSet(ClassIdOf<T>, bool),
+        /// Locked the supply of a class for good. \[class_id, total_issuance\]
+        SupplyLocked(ClassIdOf<T>, TokenIdOf<T>),
+        /// Set an attribute of a class, or of a single token. \[class_id, token_id, key, value\]
+        AttributeSet(ClassIdOf<T>, Option<TokenIdOf<T>>, Vec<u8>, Vec<u8>),
+        /// Cleared an attribute of a class, or of a single token. \[class_id, token_id, key\]
+        AttributeCleared(ClassIdOf<T>, Option<TokenIdOf<T>>, Vec<u8>),
     }
 
+    /// Account that may accept the ownership of a class, set by its current owner.
+    #[pallet::storage]
+    #[pallet::getter(fn pending_class_owner)]
+    pub type PendingClassOwner<T: Config> =
+        StorageMap<_, Twox64Concat, ClassIdOf<T>, T::AccountId, OptionQuery>;
+
+    /// Account which reserved the storage deposit of a class, and the reserved amount.
+    #[pallet::storage]
+    #[pallet::getter(fn class_deposits)]
+    pub type ClassDeposits<T: Config> =
+        StorageMap<_, Twox64Concat, ClassIdOf<T>, (T::AccountId, BalanceOf<T>), OptionQuery>;
+
+    /// Account which reserved the storage deposit of a token, and the reserved amount.
+    #[pallet::storage]
+    #[pallet::getter(fn token_deposits)]
+    pub type TokenDeposits<T: Config> = StorageDoubleMap<
+        _,
+        Twox64Concat,
+        ClassIdOf<T>,
+        Twox64Concat,
+        TokenIdOf<T>,
+        (T::AccountId, BalanceOf<T>),
+        OptionQuery,
+    >;
+
+    /// Attribute values by class and token, `None` holding the attributes of the class
+    /// itself, and key. Each value comes with the account which reserved its deposit and
+    /// the reserved amount.
+    #[pallet::storage]
+    #[pallet::getter(fn attributes)]
+    pub type Attributes<T: Config> = StorageDoubleMap<
+        _,
+        Twox64Concat,
+        (ClassIdOf<T>, Option<TokenIdOf<T>>),
+        Blake2_128Concat,
+        Vec<u8>,
+        (Vec<u8>, (T::AccountId, BalanceOf<T>)),
+        OptionQuery,
+    >;
+
+    /// Number of attributes by class and token, `None` counting the attributes of the class
+    /// itself.
+    #[pallet::storage]
+    #[pallet::getter(fn attribute_count)]
+    pub type AttributeCount<T: Config> =
+        StorageMap<_, Twox64Concat, (ClassIdOf<T>, Option<TokenIdOf<T>>), u32, ValueQuery>;
+
     #[pallet::pallet]
     pub struct Pallet<T>(_);
 
@@ -60,21 +229,51 @@ pub mod pallet {
 
     #[pallet::call]
     impl<T: Config> Pallet<T> {
-        /// Create NFT class, tokens belong to the class.
+        /// Create NFT class, tokens belong to the class. A deposit growing with the
+        /// metadata length is reserved from the caller until the class is destroyed.
         ///
         /// - `metadata`: external metadata
+        /// - `royalty`: account paid on every sale of the class tokens and its cut, at most
+        ///   `MaxRoyalty`
+        /// - `max_supply`: maximum number of tokens of the class in existence, if any
         #[pallet::weight(1_000)]
-        pub fn create_class(origin: OriginFor<T>, metadata: Vec<u8>) -> DispatchResultWithPostInfo {
+        #[transactional]
+        pub fn create_class(
+            origin: OriginFor<T>,
+            metadata: Vec<u8>,
+            royalty: Option<(T::AccountId, Permill)>,
+            max_supply: Option<u32>,
+        ) -> DispatchResultWithPostInfo {
             let owner = ensure_signed(origin)?;
+            ensure!(
+                metadata.len() <= T::MaxMetadataLength::get() as usize,
+                Error::<T>::MetadataTooLong
+            );
+            if let Some((_, rate)) = royalty {
+                ensure!(rate <= T::MaxRoyalty::get(), Error::<T>::RoyaltyTooHigh);
+            }
             let next_id = orml_nft::Pallet::<T>::next_class_id();
 
-            orml_nft::Pallet::<T>::create_class(&owner, metadata, ClassData::default())?;
+            let deposit = Self::reserve_deposit(None, &owner, Self::class_deposit(&metadata))?;
+            ClassDeposits::<T>::insert(next_id, deposit);
+            orml_nft::Pallet::<T>::create_class(
+                &owner,
+                metadata,
+                ClassData {
+                    royalty,
+                    frozen: false,
+                    holder_attributes: false,
+                    max_supply,
+                    supply_locked: false,
+                },
+            )?;
 
             Self::deposit_event(Event::CreatedClass(owner, next_id));
             Ok(().into())
         }
 
-        /// Mint NFT token
+        /// Mint NFT token, a deposit growing with the metadata length is reserved from
+        /// the caller for every token until it is burned.
         ///
         /// - `to`: the token owner's account
         /// - `class_id`: token belong to the class id
@@ -91,16 +290,7 @@ pub mod pallet {
         ) -> DispatchResultWithPostInfo {
             let who = ensure_signed(origin)?;
             let to = T::Lookup::lookup(to)?;
-            ensure!(quantity >= 1, Error::<T>::InvalidQuantity);
-            let class_info =
-                orml_nft::Pallet::<T>::classes(class_id).ok_or(Error::<T>::ClassIdNotFound)?;
-            ensure!(who == class_info.owner, Error::<T>::NoPermission);
-
-            for _ in 0..quantity {
-                orml_nft::Pallet::<T>::mint(&to, class_id, metadata.clone(), TokenData::default())?;
-            }
-
-            Self::deposit_event(Event::MintedToken(who, to, class_id, quantity));
+            Self::mint_many(&who, &to, class_id, metadata, quantity)?;
             Ok(().into())
         }
 
@@ -120,6 +310,487 @@ pub mod pallet {
             <Pallet<T> as NFT<T::AccountId>>::transfer(&who, &to, token)?;
             Ok(().into())
         }
+
+        /// Burn NFT token, only its owner can do so. The token deposit goes back to
+        /// the account which reserved it.
+        ///
+        /// - `class_id`: class of the token
+        /// - `token_id`: token to burn
+        #[pallet::weight(1_000)]
+        #[transactional]
+        pub fn burn(
+            origin: OriginFor<T>,
+            class_id: ClassIdOf<T>,
+            token_id: TokenIdOf<T>,
+        ) -> DispatchResultWithPostInfo {
+            let who = ensure_signed(origin)?;
+
+            orml_nft::Pallet::<T>::burn(&who, (class_id, token_id))?;
+            Self::unreserve_deposit(TokenDeposits::<T>::take(class_id, token_id));
+            Self::clear_attributes(class_id, Some(token_id));
+
+            Self::deposit_event(Event::Burned(who, class_id, token_id));
+            Ok(().into())
+        }
+
+        /// Destroy NFT class, only its owner can do so once every token is burned. The
+        /// class deposit goes back to the account which reserved it.
+        ///
+        /// - `class_id`: class to destroy
+        #[pallet::weight(1_000)]
+        #[transactional]
+        pub fn destroy_class(
+            origin: OriginFor<T>,
+            class_id: ClassIdOf<T>,
+        ) -> DispatchResultWithPostInfo {
+            let who = ensure_signed(origin)?;
+            let class_info =
+                orml_nft::Pallet::<T>::classes(class_id).ok_or(Error::<T>::ClassIdNotFound)?;
+            ensure!(who == class_info.owner, Error::<T>::NoPermission);
+            ensure!(
+                class_info.total_issuance.is_zero(),
+                Error::<T>::ClassNotEmpty
+            );
+
+            orml_nft::Pallet::<T>::destroy_class(&who, class_id)?;
+            PendingClassOwner::<T>::remove(class_id);
+            Self::unreserve_deposit(ClassDeposits::<T>::take(class_id));
+            Self::clear_attributes(class_id, None);
+
+            Self::deposit_event(Event::DestroyedClass(who, class_id));
+            Ok(().into())
+        }
+
+        /// Offer the ownership of a class to another account, which has to accept it
+        /// with `accept_class_ownership`. A new offer replaces the pending one.
+        ///
+        /// - `class_id`: class to hand over
+        /// - `new_owner`: account allowed to accept the ownership
+        #[pallet::weight(1_000)]
+        pub fn transfer_class_ownership(
+            origin: OriginFor<T>,
+            class_id: ClassIdOf<T>,
+            new_owner: <T::Lookup as StaticLookup>::Source,
+        ) -> DispatchResultWithPostInfo {
+            let who = ensure_signed(origin)?;
+            let new_owner = T::Lookup::lookup(new_owner)?;
+            let class_info =
+                orml_nft::Pallet::<T>::classes(class_id).ok_or(Error::<T>::ClassIdNotFound)?;
+            ensure!(who == class_info.owner, Error::<T>::NoPermission);
+
+            PendingClassOwner::<T>::insert(class_id, new_owner.clone());
+
+            Self::deposit_event(Event::ClassOwnershipOffered(who, new_owner, class_id));
+            Ok(().into())
+        }
+
+        /// Accept the ownership of a class offered to the caller
+        ///
+        /// - `class_id`: class to take over
+        #[pallet::weight(1_000)]
+        #[transactional]
+        pub fn accept_class_ownership(
+            origin: OriginFor<T>,
+            class_id: ClassIdOf<T>,
+        ) -> DispatchResultWithPostInfo {
+            let who = ensure_signed(origin)?;
+            ensure!(
+                Self::pending_class_owner(class_id).as_ref() == Some(&who),
+                Error::<T>::NoPendingOwnership
+            );
+
+            let old_owner = orml_nft::Classes::<T>::try_mutate(
+                class_id,
+                |class_info| -> Result<T::AccountId, DispatchError> {
+                    let class_info = class_info.as_mut().ok_or(Error::<T>::ClassIdNotFound)?;
+                    Ok(sp_std::mem::replace(&mut class_info.owner, who.clone()))
+                },
+            )?;
+            PendingClassOwner::<T>::remove(class_id);
+
+            Self::deposit_event(Event::ClassOwnershipTransferred(old_owner, who, class_id));
+            Ok(().into())
+        }
+
+        /// Replace the metadata of a class, only its owner can do so until it is frozen.
+        /// The class deposit is reserved again from the caller for the new metadata.
+        ///
+        /// - `class_id`: class to update
+        /// - `metadata`: new external metadata
+        #[pallet::weight(1_000)]
+        #[transactional]
+        pub fn set_class_metadata(
+            origin: OriginFor<T>,
+            class_id: ClassIdOf<T>,
+            metadata: Vec<u8>,
+        ) -> DispatchResultWithPostInfo {
+            let who = ensure_signed(origin)?;
+            ensure!(
+                metadata.len() <= T::MaxMetadataLength::get() as usize,
+                Error::<T>::MetadataTooLong
+            );
+            let amount = Self::class_deposit(&metadata);
+
+            orml_nft::Classes::<T>::try_mutate(class_id, |class_info| -> DispatchResult {
+                let class_info = class_info.as_mut().ok_or(Error::<T>::ClassIdNotFound)?;
+                ensure!(who == class_info.owner, Error::<T>::NoPermission);
+                ensure!(!class_info.data.frozen, Error::<T>::MetadataFrozen);
+                class_info.metadata = metadata;
+                Ok(())
+            })?;
+            ClassDeposits::<T>::try_mutate(class_id, |deposit| -> DispatchResult {
+                *deposit = Some(Self::reserve_deposit(deposit.take(), &who, amount)?);
+                Ok(())
+            })?;
+
+            Self::deposit_event(Event::ClassMetadataSet(class_id));
+            Ok(().into())
+        }
+
+        /// Replace the metadata of a token, only the class owner can do so until the
+        /// token or its class is frozen. The token deposit is reserved again from the
+        /// caller for the new metadata.
+        ///
+        /// - `class_id`: class of the token
+        /// - `token_id`: token to update
+        /// - `metadata`: new external metadata
+        #[pallet::weight(1_000)]
+        #[transactional]
+        pub fn set_token_metadata(
+            origin: OriginFor<T>,
+            class_id: ClassIdOf<T>,
+            token_id: TokenIdOf<T>,
+            metadata: Vec<u8>,
+        ) -> DispatchResultWithPostInfo {
+            let who = ensure_signed(origin)?;
+            ensure!(
+                metadata.len() <= T::MaxMetadataLength::get() as usize,
+                Error::<T>::MetadataTooLong
+            );
+            let amount = Self::token_deposit(&metadata);
+            let class_info =
+                orml_nft::Pallet::<T>::classes(class_id).ok_or(Error::<T>::ClassIdNotFound)?;
+            ensure!(who == class_info.owner, Error::<T>::NoPermission);
+            ensure!(!class_info.data.frozen, Error::<T>::MetadataFrozen);
+
+            orml_nft::Tokens::<T>::try_mutate(
+                class_id,
+                token_id,
+                |token_info| -> DispatchResult {
+                    let token_info = token_info.as_mut().ok_or(Error::<T>::TokenIdNotFound)?;
+                    ensure!(!token_info.data.frozen, Error::<T>::MetadataFrozen);
+                    token_info.metadata = metadata;
+                    Ok(())
+                },
+            )?;
+            TokenDeposits::<T>::try_mutate(class_id, token_id, |deposit| -> DispatchResult {
+                *deposit = Some(Self::reserve_deposit(deposit.take(), &who, amount)?);
+                Ok(())
+            })?;
+
+            Self::deposit_event(Event::TokenMetadataSet(class_id, token_id));
+            Ok(().into())
+        }
+
+        /// Freeze metadata for good, only the class owner can do so. Freezing a class
+        /// also freezes the metadata of all its tokens.
+        ///
+        /// - `class_id`: class to freeze, or class of the token
+        /// - `token_id`: token to freeze, `None` to freeze the whole class
+        #[pallet::weight(1_000)]
+        pub fn freeze_metadata(
+            origin: OriginFor<T>,
+            class_id: ClassIdOf<T>,
+            token_id: Option<TokenIdOf<T>>,
+        ) -> DispatchResultWithPostInfo {
+            let who = ensure_signed(origin)?;
+            let class_info =
+                orml_nft::Pallet::<T>::classes(class_id).ok_or(Error::<T>::ClassIdNotFound)?;
+            ensure!(who == class_info.owner, Error::<T>::NoPermission);
+
+            match token_id {
+                Some(token_id) => {
+                    orml_nft::Tokens::<T>::try_mutate(class_id, token_id, |token_info| {
+                        let token_info = token_info.as_mut().ok_or(Error::<T>::TokenIdNotFound)?;
+                        token_info.data.frozen = true;
+                        Ok::<_, Error<T>>(())
+                    })?
+                }
+                None => orml_nft::Classes::<T>::mutate(class_id, |class_info| {
+                    if let Some(class_info) = class_info {
+                        class_info.data.frozen = true;
+                    }
+                }),
+            }
+
+            Self::deposit_event(Event::MetadataFrozen(class_id, token_id));
+            Ok(().into())
+        }
+
+        /// Allow or forbid token owners to edit the attributes of their tokens, only the
+        /// class owner can do so until the class is frozen
+        ///
+        /// - `class_id`: class to configure
+        /// - `allowed`: whether token owners can edit their token attributes
+        #[pallet::weight(1_000)]
+        pub fn set_holder_attributes(
+            origin: OriginFor<T>,
+            class_id: ClassIdOf<T>,
+            allowed: bool,
+        ) -> DispatchResultWithPostInfo {
+            let who = ensure_signed(origin)?;
+
+            orml_nft::Classes::<T>::try_mutate(class_id, |class_info| -> DispatchResult {
+                let class_info = class_info.as_mut().ok_or(Error::<T>::ClassIdNotFound)?;
+                ensure!(who == class_info.owner, Error::<T>::NoPermission);
+                ensure!(!class_info.data.frozen, Error::<T>::MetadataFrozen);
+                class_info.data.holder_attributes = allowed;
+                Ok(())
+            })?;
+
+            Self::deposit_event(Event::HolderAttributesSet(class_id, allowed));
+            Ok(().into())
+        }
+
+        /// Close minting of a class for good, only its owner can do so
+        ///
+        /// - `class_id`: class to lock
+        #[pallet::weight(1_000)]
+        pub fn lock_supply(
+            origin: OriginFor<T>,
+            class_id: ClassIdOf<T>,
+        ) -> DispatchResultWithPostInfo {
+            let who = ensure_signed(origin)?;
+
+            let total_issuance = orml_nft::Classes::<T>::try_mutate(
+                class_id,
+                |class_info| -> Result<_, DispatchError> {
+                    let class_info = class_info.as_mut().ok_or(Error::<T>::ClassIdNotFound)?;
+                    ensure!(who == class_info.owner, Error::<T>::NoPermission);
+                    class_info.data.supply_locked = true;
+                    Ok(class_info.total_issuance)
+                },
+            )?;
+
+            Self::deposit_event(Event::SupplyLocked(class_id, total_issuance));
+            Ok(().into())
+        }
+
+        /// Set an attribute of a class or of a token. The class owner can edit every
+        /// attribute, token owners those of their tokens if the class allows it. A deposit
+        /// growing with the key and value length is reserved from the caller until the
+        /// attribute is cleared.
+        ///
+        /// - `class_id`: class to update, or class of the token
+        /// - `token_id`: token to update, `None` to update the class itself
+        /// - `key`: attribute key
+        /// - `value`: attribute value
+        #[pallet::weight(1_000)]
+        #[transactional]
+        pub fn set_attribute(
+            origin: OriginFor<T>,
+            class_id: ClassIdOf<T>,
+            token_id: Option<TokenIdOf<T>>,
+            key: Vec<u8>,
+            value: Vec<u8>,
+        ) -> DispatchResultWithPostInfo {
+            let who = ensure_signed(origin)?;
+            ensure!(
+                key.len() <= T::MaxAttributeKeyLength::get() as usize,
+                Error::<T>::AttributeKeyTooLong
+            );
+            ensure!(
+                value.len() <= T::MaxAttributeValueLength::get() as usize,
+                Error::<T>::AttributeValueTooLong
+            );
+            Self::ensure_can_edit_attributes(&who, class_id, token_id)?;
+
+            let amount = T::DepositPerByte::get()
+                .saturating_mul(((key.len() + value.len()) as u32).into())
+                .saturating_add(T::AttributeDeposit::get());
+            Attributes::<T>::try_mutate(
+                (class_id, token_id),
+                &key,
+                |attribute| -> DispatchResult {
+                    if attribute.is_none() {
+                        AttributeCount::<T>::try_mutate(
+                            (class_id, token_id),
+                            |count| -> DispatchResult {
+                                ensure!(
+                                    *count < T::MaxAttributes::get(),
+                                    Error::<T>::TooManyAttributes
+                                );
+                                *count += 1;
+                                Ok(())
+                            },
+                        )?;
+                    }
+                    let old = attribute.take().map(|(_, deposit)| deposit);
+                    let deposit = Self::reserve_deposit(old, &who, amount)?;
+                    *attribute = Some((value.clone(), deposit));
+                    Ok(())
+                },
+            )?;
+
+            Self::deposit_event(Event::AttributeSet(class_id, token_id, key, value));
+            Ok(().into())
+        }
+
+        /// Clear an attribute of a class or of a token, with the same permissions as
+        /// `set_attribute`. The deposit goes back to the account which reserved it.
+        ///
+        /// - `class_id`: class to update, or class of the token
+        /// - `token_id`: token to update, `None` to update the class itself
+        /// - `key`: attribute key
+        #[pallet::weight(1_000)]
+        pub fn clear_attribute(
+            origin: OriginFor<T>,
+            class_id: ClassIdOf<T>,
+            token_id: Option<TokenIdOf<T>>,
+            key: Vec<u8>,
+        ) -> DispatchResultWithPostInfo {
+            let who = ensure_signed(origin)?;
+            Self::ensure_can_edit_attributes(&who, class_id, token_id)?;
+
+            let (_, deposit) = Attributes::<T>::take((class_id, token_id), &key)
+                .ok_or(Error::<T>::AttributeNotFound)?;
+            AttributeCount::<T>::mutate((class_id, token_id), |count| {
+                *count = count.saturating_sub(1)
+            });
+            Self::unreserve_deposit(Some(deposit));
+
+            Self::deposit_event(Event::AttributeCleared(class_id, token_id, key));
+            Ok(().into())
+        }
+    }
+}
+
+impl<T: Config> Pallet<T> {
+    /// Mint `quantity` tokens of class `class_id` for `to`, `who` has to own the class.
+    /// Returns the ids of the minted tokens, in minting order.
+    #[transactional]
+    pub fn mint_many(
+        who: &T::AccountId,
+        to: &T::AccountId,
+        class_id: ClassIdOf<T>,
+        metadata: Vec<u8>,
+        quantity: u32,
+    ) -> Result<Vec<TokenIdOf<T>>, DispatchError> {
+        ensure!(quantity >= 1, Error::<T>::InvalidQuantity);
+        ensure!(
+            metadata.len() <= T::MaxMetadataLength::get() as usize,
+            Error::<T>::MetadataTooLong
+        );
+        let class_info =
+            orml_nft::Pallet::<T>::classes(class_id).ok_or(Error::<T>::ClassIdNotFound)?;
+        ensure!(*who == class_info.owner, Error::<T>::NoPermission);
+        ensure!(!class_info.data.supply_locked, Error::<T>::SupplyLocked);
+        if let Some(max_supply) = class_info.data.max_supply {
+            ensure!(
+                class_info.total_issuance.saturating_add(quantity.into())
+                    <= TokenIdOf::<T>::from(max_supply),
+                Error::<T>::MaxSupplyReached
+            );
+        }
+
+        let amount = Self::token_deposit(&metadata);
+        let token_ids = (0..quantity)
+            .map(|_| {
+                let token_id = orml_nft::Pallet::<T>::mint(
+                    to,
+                    class_id,
+                    metadata.clone(),
+                    TokenData::default(),
+                )?;
+                let deposit = Self::reserve_deposit(None, who, amount)?;
+                TokenDeposits::<T>::insert(class_id, token_id, deposit);
+                Ok(token_id)
+            })
+            .collect::<Result<Vec<_>, DispatchError>>()?;
+
+        if let (Some(first), Some(last)) = (token_ids.first(), token_ids.last()) {
+            Self::deposit_event(Event::MintedToken(
+                who.clone(),
+                to.clone(),
+                class_id,
+                *first,
+                *last,
+            ));
+        }
+        Ok(token_ids)
+    }
+
+    /// Deposit for storing a class with `metadata`.
+    fn class_deposit(metadata: &[u8]) -> BalanceOf<T> {
+        T::DepositPerByte::get()
+            .saturating_mul((metadata.len() as u32).into())
+            .saturating_add(T::ClassDeposit::get())
+    }
+
+    /// Deposit for storing a token with `metadata`.
+    fn token_deposit(metadata: &[u8]) -> BalanceOf<T> {
+        T::DepositPerByte::get()
+            .saturating_mul((metadata.len() as u32).into())
+            .saturating_add(T::TokenDeposit::get())
+    }
+
+    /// Check that `who` can edit the attributes of the class, or of the token if
+    /// `token_id` is given, and that its metadata isn't frozen.
+    fn ensure_can_edit_attributes(
+        who: &T::AccountId,
+        class_id: ClassIdOf<T>,
+        token_id: Option<TokenIdOf<T>>,
+    ) -> DispatchResult {
+        let class_info =
+            orml_nft::Pallet::<T>::classes(class_id).ok_or(Error::<T>::ClassIdNotFound)?;
+        ensure!(!class_info.data.frozen, Error::<T>::MetadataFrozen);
+
+        match token_id {
+            Some(token_id) => {
+                let token_info = orml_nft::Pallet::<T>::tokens(class_id, token_id)
+                    .ok_or(Error::<T>::TokenIdNotFound)?;
+                ensure!(!token_info.data.frozen, Error::<T>::MetadataFrozen);
+                ensure!(
+                    *who == class_info.owner
+                        || (class_info.data.holder_attributes && *who == token_info.owner),
+                    Error::<T>::NoPermission
+                );
+            }
+            None => ensure!(*who == class_info.owner, Error::<T>::NoPermission),
+        }
+        Ok(())
+    }
+
+    /// Remove every attribute of the class, or of the token if `token_id` is given, and
+    /// give their deposits back. There are at most `MaxAttributes` of them.
+    fn clear_attributes(class_id: ClassIdOf<T>, token_id: Option<TokenIdOf<T>>) {
+        AttributeCount::<T>::remove((class_id, token_id));
+        for (_, deposit) in Attributes::<T>::drain_prefix((class_id, token_id)) {
+            Self::unreserve_deposit(Some(deposit));
+        }
+    }
+
+    /// Release the `old` deposit and reserve `amount` from `who` instead.
+    fn reserve_deposit(
+        old: Option<(T::AccountId, BalanceOf<T>)>,
+        who: &T::AccountId,
+        amount: BalanceOf<T>,
+    ) -> Result<(T::AccountId, BalanceOf<T>), DispatchError> {
+        Self::unreserve_deposit(old);
+        T::Currency::reserve(who, amount)?;
+        Ok((who.clone(), amount))
+    }
+
+    /// Give a deposit back to the account which reserved it.
+    fn unreserve_deposit(deposit: Option<(T::AccountId, BalanceOf<T>)>) {
+        if let Some((who, amount)) = deposit {
+            T::Currency::unreserve(&who, amount);
+        }
+    }
+
+    /// Royalty recipient and cut of class `class_id`, if it has any.
+    pub fn royalty(class_id: ClassIdOf<T>) -> Option<(T::AccountId, Permill)> {
+        orml_nft::Pallet::<T>::classes(class_id).and_then(|class| class.data.royalty)
     }
 }
 
@@ -141,6 +812,13 @@ impl<T: Config> NFT<T::AccountId> for Pallet<T> {
         to: &T::AccountId,
         token: (Self::ClassId, Self::TokenId),
     ) -> DispatchResult {
-        orml_nft::Pallet::<T>::transfer(from, to, token)
+        orml_nft::Pallet::<T>::transfer(from, to, token)?;
+        Self::deposit_event(Event::Transferred(
+            from.clone(),
+            to.clone(),
+            token.0,
+            token.1,
+        ));
+        Ok(())
     }
 }
@@ -0,0 +1,544 @@
+//! Unit tests for the nft pallet.
+
+use crate::{mock::*, Error};
+use frame_support::{
+    assert_noop, assert_ok,
+    dispatch::DispatchResultWithPostInfo,
+    traits::{Currency, ReservableCurrency},
+};
+
+type NftEvent = crate::Event<Runtime>;
+
+fn has_event(event: NftEvent) -> bool {
+    let event: Event = event.into();
+    System::events().iter().any(|record| record.event == event)
+}
+
+/// Create a class of `ALICE` with `metadata`, without royalty nor maximum supply.
+fn create_class(metadata: &[u8]) -> ClassId {
+    let class_id = OrmlNFT::next_class_id();
+    assert_ok!(NFT::create_class(
+        Origin::signed(ALICE),
+        metadata.to_vec(),
+        None,
+        None
+    ));
+    class_id
+}
+
+#[test]
+fn class_deposit_grows_with_the_metadata() {
+    new_test_ext().execute_with(|| {
+        assert_noop!(
+            NFT::create_class(Origin::signed(ALICE), vec![0; 9], None, None),
+            Error::<Runtime>::MetadataTooLong
+        );
+
+        let class_id = create_class(b"abc");
+
+        assert_eq!(Balances::reserved_balance(&ALICE), 13);
+        assert_eq!(NFT::class_deposits(class_id), Some((ALICE, 13)));
+        assert!(has_event(NftEvent::CreatedClass(ALICE, class_id)));
+    });
+}
+
+#[test]
+fn class_cannot_be_created_without_the_deposit() {
+    new_test_ext().execute_with(|| {
+        assert_noop!(
+            NFT::create_class(Origin::signed(EVE), Vec::new(), None, None),
+            pallet_balances::Error::<Runtime>::InsufficientBalance
+        );
+    });
+}
+
+#[test]
+fn minter_reserves_a_deposit_for_every_token() {
+    new_test_ext().execute_with(|| {
+        let class_id = create_class(b"abc");
+
+        assert_ok!(NFT::mint(
+            Origin::signed(ALICE),
+            BOB,
+            class_id,
+            b"ab".to_vec(),
+            2
+        ));
+
+        assert_eq!(Balances::reserved_balance(&ALICE), 13 + 2 * 7);
+        assert_eq!(Balances::reserved_balance(&BOB), 0);
+        assert_eq!(NFT::token_deposits(class_id, 0), Some((ALICE, 7)));
+        assert_eq!(NFT::token_deposits(class_id, 1), Some((ALICE, 7)));
+        assert!(has_event(NftEvent::MintedToken(ALICE, BOB, class_id, 0, 1)));
+    });
+}
+
+#[test]
+fn metadata_update_reserves_the_new_deposit() {
+    new_test_ext().execute_with(|| {
+        let class_id = create_class(b"abc");
+        assert_ok!(NFT::mint(
+            Origin::signed(ALICE),
+            ALICE,
+            class_id,
+            b"ab".to_vec(),
+            1
+        ));
+
+        assert_ok!(NFT::set_class_metadata(
+            Origin::signed(ALICE),
+            class_id,
+            b"abcdef".to_vec()
+        ));
+        assert_eq!(NFT::class_deposits(class_id), Some((ALICE, 16)));
+        assert_eq!(Balances::reserved_balance(&ALICE), 16 + 7);
+
+        assert_ok!(NFT::set_token_metadata(
+            Origin::signed(ALICE),
+            class_id,
+            0,
+            Vec::new()
+        ));
+        assert_eq!(NFT::token_deposits(class_id, 0), Some((ALICE, 5)));
+        assert_eq!(Balances::reserved_balance(&ALICE), 16 + 5);
+
+        assert_noop!(
+            NFT::set_token_metadata(Origin::signed(ALICE), class_id, 0, vec![0; 9]),
+            Error::<Runtime>::MetadataTooLong
+        );
+    });
+}
+
+#[test]
+fn burn_refunds_the_minter() {
+    new_test_ext().execute_with(|| {
+        let class_id = create_class(b"abc");
+        assert_ok!(NFT::mint(
+            Origin::signed(ALICE),
+            BOB,
+            class_id,
+            b"ab".to_vec(),
+            1
+        ));
+
+        assert_noop!(
+            NFT::burn(Origin::signed(ALICE), class_id, 0),
+            orml_nft::Error::<Runtime>::NoPermission
+        );
+        assert_ok!(NFT::burn(Origin::signed(BOB), class_id, 0));
+
+        assert_eq!(Balances::reserved_balance(&ALICE), 13);
+        assert_eq!(Balances::free_balance(&BOB), INITIAL_BALANCE);
+        assert!(NFT::token_deposits(class_id, 0).is_none());
+    });
+}
+
+#[test]
+fn destroy_class_refunds_the_class_deposit() {
+    new_test_ext().execute_with(|| {
+        let class_id = create_class(b"abc");
+        assert_ok!(NFT::mint(
+            Origin::signed(ALICE),
+            ALICE,
+            class_id,
+            Vec::new(),
+            1
+        ));
+
+        assert_noop!(
+            NFT::destroy_class(Origin::signed(ALICE), class_id),
+            Error::<Runtime>::ClassNotEmpty
+        );
+        assert_ok!(NFT::burn(Origin::signed(ALICE), class_id, 0));
+        assert_ok!(NFT::destroy_class(Origin::signed(ALICE), class_id));
+
+        assert_eq!(Balances::reserved_balance(&ALICE), 0);
+        assert_eq!(Balances::free_balance(&ALICE), INITIAL_BALANCE);
+        assert!(NFT::class_deposits(class_id).is_none());
+    });
+}
+
+#[test]
+fn class_ownership_needs_to_be_accepted() {
+    new_test_ext().execute_with(|| {
+        let class_id = create_class(b"");
+
+        assert_noop!(
+            NFT::transfer_class_ownership(Origin::signed(BOB), class_id, BOB),
+            Error::<Runtime>::NoPermission
+        );
+        assert_ok!(NFT::transfer_class_ownership(
+            Origin::signed(ALICE),
+            class_id,
+            BOB
+        ));
+        assert_eq!(OrmlNFT::classes(class_id).unwrap().owner, ALICE);
+
+        assert_noop!(
+            NFT::accept_class_ownership(Origin::signed(CHARLIE), class_id),
+            Error::<Runtime>::NoPendingOwnership
+        );
+        assert_ok!(NFT::accept_class_ownership(Origin::signed(BOB), class_id));
+
+        assert_eq!(OrmlNFT::classes(class_id).unwrap().owner, BOB);
+        assert!(NFT::pending_class_owner(class_id).is_none());
+        assert!(has_event(NftEvent::ClassOwnershipTransferred(
+            ALICE, BOB, class_id
+        )));
+    });
+}
+
+#[test]
+fn previous_owner_gets_the_class_deposit_back() {
+    new_test_ext().execute_with(|| {
+        let class_id = create_class(b"abc");
+        assert_ok!(NFT::transfer_class_ownership(
+            Origin::signed(ALICE),
+            class_id,
+            BOB
+        ));
+        assert_ok!(NFT::accept_class_ownership(Origin::signed(BOB), class_id));
+        assert_eq!(Balances::reserved_balance(&ALICE), 13);
+
+        assert_ok!(NFT::destroy_class(Origin::signed(BOB), class_id));
+
+        assert_eq!(Balances::reserved_balance(&ALICE), 0);
+        assert_eq!(Balances::free_balance(&ALICE), INITIAL_BALANCE);
+        assert_eq!(Balances::free_balance(&BOB), INITIAL_BALANCE);
+    });
+}
+
+#[test]
+fn deposits_move_to_the_new_owner_as_they_are_reserved_again() {
+    new_test_ext().execute_with(|| {
+        let class_id = create_class(b"abc");
+        assert_ok!(NFT::mint(
+            Origin::signed(ALICE),
+            ALICE,
+            class_id,
+            Vec::new(),
+            1
+        ));
+        assert_ok!(NFT::transfer_class_ownership(
+            Origin::signed(ALICE),
+            class_id,
+            BOB
+        ));
+        assert_ok!(NFT::accept_class_ownership(Origin::signed(BOB), class_id));
+
+        assert_noop!(
+            NFT::mint(Origin::signed(ALICE), ALICE, class_id, Vec::new(), 1),
+            Error::<Runtime>::NoPermission
+        );
+        assert_ok!(NFT::mint(Origin::signed(BOB), BOB, class_id, Vec::new(), 1));
+        assert_ok!(NFT::set_class_metadata(
+            Origin::signed(BOB),
+            class_id,
+            Vec::new()
+        ));
+
+        // only the token ALICE minted is still on her
+        assert_eq!(Balances::reserved_balance(&ALICE), 5);
+        assert_eq!(Balances::reserved_balance(&BOB), 5 + 10);
+        assert_eq!(NFT::class_deposits(class_id), Some((BOB, 10)));
+
+        assert_ok!(NFT::burn(Origin::signed(ALICE), class_id, 0));
+        assert_ok!(NFT::burn(Origin::signed(BOB), class_id, 1));
+        assert_ok!(NFT::destroy_class(Origin::signed(BOB), class_id));
+        assert_eq!(Balances::free_balance(&ALICE), INITIAL_BALANCE);
+        assert_eq!(Balances::free_balance(&BOB), INITIAL_BALANCE);
+    });
+}
+
+/// Create a class of `ALICE` and mint its first token for `BOB`.
+fn mint_for_bob() -> ClassId {
+    let class_id = create_class(b"");
+    assert_ok!(NFT::mint(
+        Origin::signed(ALICE),
+        BOB,
+        class_id,
+        Vec::new(),
+        1
+    ));
+    class_id
+}
+
+fn set_attribute(
+    who: AccountId,
+    class_id: ClassId,
+    token_id: Option<TokenId>,
+    key: &[u8],
+    value: &[u8],
+) -> DispatchResultWithPostInfo {
+    NFT::set_attribute(
+        Origin::signed(who),
+        class_id,
+        token_id,
+        key.to_vec(),
+        value.to_vec(),
+    )
+}
+
+fn attribute(
+    class_id: ClassId,
+    token_id: Option<TokenId>,
+    key: &[u8],
+) -> Option<(Vec<u8>, (AccountId, Balance))> {
+    NFT::attributes((class_id, token_id), key.to_vec())
+}
+
+fn attribute_count(class_id: ClassId, token_id: Option<TokenId>) -> u32 {
+    NFT::attribute_count((class_id, token_id))
+}
+
+#[test]
+fn attribute_deposit_grows_with_key_and_value() {
+    new_test_ext().execute_with(|| {
+        let class_id = create_class(b"");
+
+        assert_ok!(set_attribute(ALICE, class_id, None, b"k", b"v"));
+        assert_eq!(
+            attribute(class_id, None, b"k"),
+            Some((b"v".to_vec(), (ALICE, 5)))
+        );
+        assert_eq!(Balances::reserved_balance(&ALICE), 10 + 5);
+        assert_eq!(attribute_count(class_id, None), 1);
+        assert!(has_event(NftEvent::AttributeSet(
+            class_id,
+            None,
+            b"k".to_vec(),
+            b"v".to_vec()
+        )));
+
+        // overwriting replaces the deposit
+        assert_ok!(set_attribute(ALICE, class_id, None, b"k", b"vvv"));
+        assert_eq!(Balances::reserved_balance(&ALICE), 10 + 7);
+        assert_eq!(attribute_count(class_id, None), 1);
+
+        assert_noop!(
+            set_attribute(ALICE, class_id, None, b"kkkkk", b"v"),
+            Error::<Runtime>::AttributeKeyTooLong
+        );
+        assert_noop!(
+            set_attribute(ALICE, class_id, None, b"k", &[0; 9]),
+            Error::<Runtime>::AttributeValueTooLong
+        );
+    });
+}
+
+#[test]
+fn attributes_are_bounded_per_class_and_token() {
+    new_test_ext().execute_with(|| {
+        let class_id = mint_for_bob();
+        assert_ok!(set_attribute(ALICE, class_id, None, b"a", b""));
+        assert_ok!(set_attribute(ALICE, class_id, None, b"b", b""));
+
+        assert_noop!(
+            set_attribute(ALICE, class_id, None, b"c", b""),
+            Error::<Runtime>::TooManyAttributes
+        );
+        // existing keys can still be updated, and tokens have their own count
+        assert_ok!(set_attribute(ALICE, class_id, None, b"a", b"v"));
+        assert_ok!(set_attribute(ALICE, class_id, Some(0), b"c", b""));
+        assert_eq!(attribute_count(class_id, Some(0)), 1);
+
+        assert_ok!(NFT::clear_attribute(
+            Origin::signed(ALICE),
+            class_id,
+            None,
+            b"a".to_vec()
+        ));
+        assert_eq!(attribute_count(class_id, None), 1);
+        assert_ok!(set_attribute(ALICE, class_id, None, b"c", b""));
+    });
+}
+
+#[test]
+fn token_owners_edit_attributes_only_if_the_class_allows_it() {
+    new_test_ext().execute_with(|| {
+        let class_id = mint_for_bob();
+
+        assert_noop!(
+            set_attribute(BOB, class_id, Some(0), b"k", b"v"),
+            Error::<Runtime>::NoPermission
+        );
+        assert_noop!(
+            NFT::set_holder_attributes(Origin::signed(BOB), class_id, true),
+            Error::<Runtime>::NoPermission
+        );
+        assert_ok!(NFT::set_holder_attributes(
+            Origin::signed(ALICE),
+            class_id,
+            true
+        ));
+        assert!(has_event(NftEvent::HolderAttributesSet(class_id, true)));
+
+        assert_ok!(set_attribute(BOB, class_id, Some(0), b"k", b"v"));
+        assert_eq!(Balances::reserved_balance(&BOB), 5);
+        // the class attributes stay with the class owner
+        assert_noop!(
+            set_attribute(BOB, class_id, None, b"k", b"v"),
+            Error::<Runtime>::NoPermission
+        );
+        assert_noop!(
+            set_attribute(CHARLIE, class_id, Some(0), b"k", b"v"),
+            Error::<Runtime>::NoPermission
+        );
+    });
+}
+
+#[test]
+fn frozen_metadata_freezes_attributes() {
+    new_test_ext().execute_with(|| {
+        let class_id = mint_for_bob();
+        assert_ok!(set_attribute(ALICE, class_id, None, b"k", b"v"));
+
+        assert_ok!(NFT::freeze_metadata(
+            Origin::signed(ALICE),
+            class_id,
+            Some(0)
+        ));
+        assert_noop!(
+            set_attribute(ALICE, class_id, Some(0), b"k", b"v"),
+            Error::<Runtime>::MetadataFrozen
+        );
+        assert_ok!(set_attribute(ALICE, class_id, None, b"k", b"w"));
+
+        assert_ok!(NFT::freeze_metadata(Origin::signed(ALICE), class_id, None));
+        assert_noop!(
+            set_attribute(ALICE, class_id, None, b"k", b"v"),
+            Error::<Runtime>::MetadataFrozen
+        );
+        assert_noop!(
+            NFT::clear_attribute(Origin::signed(ALICE), class_id, None, b"k".to_vec()),
+            Error::<Runtime>::MetadataFrozen
+        );
+        assert_noop!(
+            NFT::set_holder_attributes(Origin::signed(ALICE), class_id, true),
+            Error::<Runtime>::MetadataFrozen
+        );
+    });
+}
+
+#[test]
+fn clearing_an_attribute_refunds_whoever_set_it() {
+    new_test_ext().execute_with(|| {
+        let class_id = mint_for_bob();
+        assert_ok!(NFT::set_holder_attributes(
+            Origin::signed(ALICE),
+            class_id,
+            true
+        ));
+        assert_ok!(set_attribute(BOB, class_id, Some(0), b"k", b"v"));
+
+        assert_ok!(NFT::clear_attribute(
+            Origin::signed(ALICE),
+            class_id,
+            Some(0),
+            b"k".to_vec()
+        ));
+
+        assert_eq!(Balances::reserved_balance(&BOB), 0);
+        assert_eq!(attribute_count(class_id, Some(0)), 0);
+        assert!(has_event(NftEvent::AttributeCleared(
+            class_id,
+            Some(0),
+            b"k".to_vec()
+        )));
+        assert_noop!(
+            NFT::clear_attribute(Origin::signed(ALICE), class_id, Some(0), b"k".to_vec()),
+            Error::<Runtime>::AttributeNotFound
+        );
+    });
+}
+
+#[test]
+fn burn_and_destroy_clear_the_attributes() {
+    new_test_ext().execute_with(|| {
+        let class_id = mint_for_bob();
+        assert_ok!(NFT::set_holder_attributes(
+            Origin::signed(ALICE),
+            class_id,
+            true
+        ));
+        assert_ok!(set_attribute(BOB, class_id, Some(0), b"k", b"v"));
+        assert_ok!(set_attribute(ALICE, class_id, None, b"k", b"v"));
+
+        assert_ok!(NFT::burn(Origin::signed(BOB), class_id, 0));
+        assert_eq!(Balances::reserved_balance(&BOB), 0);
+        assert!(attribute(class_id, Some(0), b"k").is_none());
+        assert_eq!(attribute_count(class_id, Some(0)), 0);
+
+        assert_ok!(NFT::destroy_class(Origin::signed(ALICE), class_id));
+        assert_eq!(Balances::reserved_balance(&ALICE), 0);
+        assert!(attribute(class_id, None, b"k").is_none());
+        assert_eq!(attribute_count(class_id, None), 0);
+    });
+}
+
+#[test]
+fn minting_stops_at_the_max_supply() {
+    new_test_ext().execute_with(|| {
+        let class_id = OrmlNFT::next_class_id();
+        assert_ok!(NFT::create_class(
+            Origin::signed(ALICE),
+            Vec::new(),
+            None,
+            Some(2)
+        ));
+
+        assert_noop!(
+            NFT::mint(Origin::signed(ALICE), BOB, class_id, Vec::new(), 3),
+            Error::<Runtime>::MaxSupplyReached
+        );
+        assert_ok!(NFT::mint(
+            Origin::signed(ALICE),
+            BOB,
+            class_id,
+            Vec::new(),
+            2
+        ));
+        assert_noop!(
+            NFT::mint(Origin::signed(ALICE), BOB, class_id, Vec::new(), 1),
+            Error::<Runtime>::MaxSupplyReached
+        );
+
+        // Burned tokens free their slot
+        assert_ok!(NFT::burn(Origin::signed(BOB), class_id, 0));
+        assert_ok!(NFT::mint(
+            Origin::signed(ALICE),
+            BOB,
+            class_id,
+            Vec::new(),
+            1
+        ));
+        assert_eq!(OrmlNFT::classes(class_id).unwrap().total_issuance, 2);
+    });
+}
+
+#[test]
+fn only_the_class_owner_locks_the_supply() {
+    new_test_ext().execute_with(|| {
+        let class_id = create_class(b"");
+        assert_ok!(NFT::mint(
+            Origin::signed(ALICE),
+            BOB,
+            class_id,
+            Vec::new(),
+            1
+        ));
+
+        assert_noop!(
+            NFT::lock_supply(Origin::signed(BOB), class_id),
+            Error::<Runtime>::NoPermission
+        );
+        assert_ok!(NFT::lock_supply(Origin::signed(ALICE), class_id));
+
+        assert!(OrmlNFT::classes(class_id).unwrap().data.supply_locked);
+        assert!(has_event(NftEvent::SupplyLocked(class_id, 1)));
+        assert_noop!(
+            NFT::mint(Origin::signed(ALICE), BOB, class_id, Vec::new(), 1),
+            Error::<Runtime>::SupplyLocked
+        );
+    });
+}
@@ -74,9 +74,12 @@ construct_runtime!(
         Ping: cumulus_ping::{Pallet, Call, Storage, Event<T>},
 
         OrmlVesting: orml_vesting::{Pallet, Storage, Call, Event<T>, Config<T>},
+        Tokens: orml_tokens::{Pallet, Storage, Event<T>},
+        Currencies: orml_currencies::{Pallet, Call, Event<T>},
 
         AuctionManager: kodadot_auction::{Pallet, Storage, Call, Event<T>},
-        NFT: kodadot_nft::{Pallet, Call, Event<T>},
+        Marketplace: kodadot_marketplace::{Pallet, Storage, Call, Event<T>},
+        NFT: kodadot_nft::{Pallet, Call, Storage, Event<T>},
         Auction: orml_auction::{Pallet, Storage, Call, Event<T>},
         OrmlNFT: orml_nft::{Pallet, Storage, Config<T>},
     }
@@ -192,6 +195,12 @@ impl_runtime_apis! {
         }
     }
 
+    impl kodadot_auction_runtime_api::AuctionApi<Block, u32, Balance> for Runtime {
+        fn current_price(id: u32) -> Option<Balance> {
+            AuctionManager::current_price(id)
+        }
+    }
+
     // posing issues on latest cumulus and substrate branches
     // impl pallet_transaction_payment_rpc_runtime_api::TransactionPaymentApi<Block, Balance> for Runtime {
     //     fn query_info(
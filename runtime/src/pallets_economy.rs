@@ -1,13 +1,16 @@
 use crate::{
-    constants::UNITS,
-    primitives::{AccountId, Balance},
-    Balances, Event, Runtime, System, Treasury,
+    constants::{CENTS, UNITS},
+    pallets_finance::TreasuryAccount,
+    primitives::{AccountId, Amount, Balance, BlockNumber, CurrencyId},
+    Balances, Event, Runtime, System, Tokens, Treasury,
 };
 use frame_support::{
     parameter_types,
     traits::{Currency, OnUnbalanced},
     weights::IdentityFee,
 };
+use orml_currencies::BasicCurrencyAdapter;
+use orml_traits::parameter_type_with_key;
 
 parameter_types! {
     /// Same as Polkadot Relay Chain.
@@ -27,6 +30,42 @@ impl pallet_balances::Config for Runtime {
     type WeightInfo = pallet_balances::weights::SubstrateWeight<Runtime>;
 }
 
+parameter_types! {
+    /// Relay chain tokens arriving over XCM are minted into `Balances` by the
+    /// `LocalAssetTransactor`, they share the native currency id.
+    pub const GetNativeCurrencyId: CurrencyId = 0;
+    /// Existential deposit of the `orml_tokens` currencies without a dedicated one.
+    pub const TokenExistentialDeposit: Balance = 1 * CENTS;
+}
+
+parameter_type_with_key! {
+    pub ExistentialDeposits: |currency_id: CurrencyId| -> Balance {
+        match *currency_id {
+            id if id == GetNativeCurrencyId::get() => ExistentialDeposit::get(),
+            _ => TokenExistentialDeposit::get(),
+        }
+    };
+}
+
+impl orml_tokens::Config for Runtime {
+    type Event = Event;
+    type Balance = Balance;
+    type Amount = Amount;
+    type CurrencyId = CurrencyId;
+    type WeightInfo = ();
+    type ExistentialDeposits = ExistentialDeposits;
+    /// Dust left by an account going below the existential deposit goes to the treasury.
+    type OnDust = orml_tokens::TransferDust<Runtime, TreasuryAccount>;
+}
+
+impl orml_currencies::Config for Runtime {
+    type Event = Event;
+    type MultiCurrency = Tokens;
+    type NativeCurrency = BasicCurrencyAdapter<Runtime, Balances, Amount, BlockNumber>;
+    type GetNativeCurrencyId = GetNativeCurrencyId;
+    type WeightInfo = ();
+}
+
 parameter_types! {
     pub const TransactionByteFee: Balance = 1 ;
 }
@@ -5,7 +5,7 @@ use crate::{
 };
 use frame_support::{parameter_types, PalletId};
 use frame_system::EnsureRoot;
-use sp_runtime::{Percent, Permill};
+use sp_runtime::{traits::AccountIdConversion, Percent, Permill};
 
 parameter_types! {
     pub const ProposalBond: Permill = Permill::from_percent(5);
@@ -19,6 +19,7 @@ parameter_types! {
     pub const BountyDepositBase: Balance = 1 * UNITS;
     pub const BountyDepositPayoutDelay: BlockNumber = 1 * DAYS;
     pub const TreasuryPalletId: PalletId = PalletId(*b"py/trsry");
+    pub TreasuryAccount: AccountId = TreasuryPalletId::get().into_account();
     pub const BountyUpdatePeriod: BlockNumber = 14 * DAYS;
     pub const MaximumReasonLength: u32 = 16384;
     pub const BountyCuratorDeposit: Permill = Permill::from_percent(50);
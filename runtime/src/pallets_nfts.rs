@@ -1,14 +1,58 @@
-use crate::{primitives::Balance, AuctionManager, Balances, Event, Runtime};
+use crate::{
+    constants::{deposit, DAYS, MINUTES},
+    pallets_finance::TreasuryAccount,
+    primitives::{AccountId, Balance, BlockNumber},
+    AuctionManager, Balances, Currencies, Event, Runtime,
+};
 use frame_support::{parameter_types, PalletId};
+use sp_runtime::Permill;
 
 parameter_types! {
     pub const AuctionPalletId: PalletId = PalletId(*b"kod/auct");
+    pub const MinimumBidIncrement: Permill = Permill::from_percent(5);
+    pub const ExtensionWindow: BlockNumber = 5 * MINUTES;
+    pub const MaxExtension: BlockNumber = 60 * MINUTES;
+    pub const MaxSealedBids: u32 = 100;
+    pub const MarketplaceFee: Permill = Permill::from_percent(2);
+    pub const MaxMarketplaceFee: Permill = Permill::from_percent(10);
+    pub const MinAuctionDuration: BlockNumber = 10 * MINUTES;
+    pub const MaxAuctionDuration: BlockNumber = 30 * DAYS;
+    pub const MaxBundleSize: u32 = 10;
+    pub const SettlementGracePeriod: BlockNumber = 7 * DAYS;
+    pub const MarketplacePalletId: PalletId = PalletId(*b"kod/mrkt");
+    pub const NftClassDeposit: Balance = deposit(1, 0);
+    // Token info and the owner index entry.
+    pub const NftTokenDeposit: Balance = deposit(2, 0);
+    pub const NftDepositPerByte: Balance = deposit(0, 1);
+    pub const MaxMetadataLength: u32 = 256;
+    pub const MaxRoyalty: Permill = Permill::from_percent(50);
+    pub const NftAttributeDeposit: Balance = deposit(1, 0);
+    pub const MaxAttributeKeyLength: u32 = 64;
+    pub const MaxAttributeValueLength: u32 = 256;
+    pub const MaxAttributes: u32 = 32;
 }
 
 impl kodadot_auction::Config for Runtime {
     type Event = Event;
-    type Currency = Balances;
+    type MultiCurrency = Currencies;
+    type TreasuryAccount = TreasuryAccount;
     type PalletId = AuctionPalletId;
+    type MinimumBidIncrement = MinimumBidIncrement;
+    type ExtensionWindow = ExtensionWindow;
+    type MaxExtension = MaxExtension;
+    type MaxSealedBids = MaxSealedBids;
+    type MarketplaceFee = MarketplaceFee;
+    type MaxMarketplaceFee = MaxMarketplaceFee;
+    type MinAuctionDuration = MinAuctionDuration;
+    type MaxAuctionDuration = MaxAuctionDuration;
+    type MaxBundleSize = MaxBundleSize;
+    type SettlementGracePeriod = SettlementGracePeriod;
+}
+
+impl kodadot_marketplace::Config for Runtime {
+    type Event = Event;
+    type Currency = Balances;
+    type PalletId = MarketplacePalletId;
 }
 
 impl orml_auction::Config for Runtime {
@@ -21,11 +65,21 @@ impl orml_auction::Config for Runtime {
 
 impl kodadot_nft::Config for Runtime {
     type Event = Event;
+    type Currency = Balances;
+    type ClassDeposit = NftClassDeposit;
+    type TokenDeposit = NftTokenDeposit;
+    type DepositPerByte = NftDepositPerByte;
+    type MaxMetadataLength = MaxMetadataLength;
+    type MaxRoyalty = MaxRoyalty;
+    type AttributeDeposit = NftAttributeDeposit;
+    type MaxAttributeKeyLength = MaxAttributeKeyLength;
+    type MaxAttributeValueLength = MaxAttributeValueLength;
+    type MaxAttributes = MaxAttributes;
 }
 
 impl orml_nft::Config for Runtime {
     type ClassId = u32;
     type TokenId = u64;
-    type ClassData = kodadot_nft::ClassData;
+    type ClassData = kodadot_nft::ClassData<AccountId>;
     type TokenData = kodadot_nft::TokenData;
 }
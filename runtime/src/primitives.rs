@@ -21,6 +21,12 @@ pub type AccountIndex = u32;
 /// Balance of an account.
 pub type Balance = u128;
 
+/// Signed version of `Balance`, used by `orml_tokens` to express balance changes.
+pub type Amount = i128;
+
+/// Identifier of a currency, the native one or a token of `orml_tokens`.
+pub type CurrencyId = u32;
+
 /// Index of a transaction in the chain.
 pub type Index = u32;
 